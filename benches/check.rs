@@ -0,0 +1,60 @@
+//! Benchmark the validation of a large number of PRDoc files.
+//!
+//! A synthetic folder containing `PRDOC_BENCH_FILES` (default: 2000) PRDoc files is generated in
+//! the temporary directory before running the benchmarks.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use prdoclib::{commands::load::LoadCmd, docfile::DocFile, schema::Schema};
+use std::{fs, path::PathBuf};
+
+const SCHEMA: &str = "./tests/data/sample_schema.json";
+const SAMPLE: &str = "./tests/data/some/pr_1235 - junk.prdoc";
+
+fn synthetic_folder() -> PathBuf {
+	let count: usize = std::env::var("PRDOC_BENCH_FILES")
+		.ok()
+		.and_then(|s| s.parse().ok())
+		.unwrap_or(2000);
+
+	let dir = std::env::temp_dir().join(format!("prdoc-bench-{count}"));
+	if dir.exists() {
+		fs::remove_dir_all(&dir).expect("Cleaning up the bench folder");
+	}
+	fs::create_dir_all(&dir).expect("Creating the bench folder");
+
+	let sample = fs::read_to_string(SAMPLE).expect("Reading the sample prdoc");
+	for n in 0..count {
+		fs::write(dir.join(format!("pr_{n}_sample.prdoc")), &sample).expect("Writing a prdoc");
+	}
+	dir
+}
+
+fn bench_check(c: &mut Criterion) {
+	let dir = synthetic_folder();
+	let schema = Schema::new(SCHEMA).expect("The sample schema is valid");
+
+	c.bench_function("schema_compile", |b| b.iter(|| Schema::new(SCHEMA).unwrap()));
+
+	let mut group = c.benchmark_group("folder");
+	group.sample_size(10);
+
+	group.bench_function("check", |b| {
+		b.iter(|| {
+			let mut files = DocFile::find(schema.clone(), &dir, false).unwrap();
+			assert!(files.all(|f| schema.check_file(&f)));
+		})
+	});
+
+	group.bench_function("load", |b| {
+		let load_cmd = LoadCmd::new(schema.clone());
+		b.iter(|| {
+			let files = DocFile::find(schema.clone(), &dir, false).unwrap();
+			files.map(|f| load_cmd.load_file(&f).unwrap()).collect::<Vec<_>>()
+		})
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, bench_check);
+criterion_main!(benches);
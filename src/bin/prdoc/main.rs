@@ -4,41 +4,80 @@
 
 mod opts;
 
-use clap::{crate_name, crate_version, Parser};
+use clap::{crate_name, crate_version, CommandFactory, Parser};
 use env_logger::Env;
 use opts::*;
 use prdoclib::{
 	commands::{
-		check::{CheckCmd, CheckResult},
+		check::{ChangedPRDocs, CheckCmd, CheckErrorKind, CheckReport, CheckStatus, FixMode},
 		generate::GenerateCmd,
-		load::LoadCmd,
+		load::{LoadCmd, SortBy},
+		migrate::MigrateCmd,
 		scan::ScanCmd,
+		schema::SchemaCmd,
+		semver_check::{SemverCheckCmd, SemverIssue},
+		summary::SummaryCmd,
 		version::VersionCmd,
 	},
 	common::PRNumber,
 	config::Config,
-	prdoc_source::{PRDocSource, PRDocSource::File},
-	schema::Schema,
+	diagnostic::Severity,
+	error::PRdocLibError,
+	export,
+	filter::Filter,
+	git::Git,
+	report,
+	schema::SchemaSource,
+	select::Selector,
+	utils::get_project_root,
 };
-use std::{cmp::Ordering, collections::HashSet, env, path::PathBuf};
+use std::{env, path::PathBuf};
 
-/// Main entry point of the cli
-fn main() -> color_eyre::Result<()> {
+/// Schema files passed on the command line are relative to the current directory
+fn schema_from_cli(schema: Option<SchemaSource>) -> Option<SchemaSource> {
+	schema.map(|source| match source {
+		SchemaSource::File(path) if path.is_relative() =>
+			SchemaSource::File(env::current_dir().map(|dir| dir.join(&path)).unwrap_or(path)),
+		source => source,
+	})
+}
+
+/// Main entry point of the cli. The errors are reported with an exit code depending on their
+/// kind, see [PRdocLibError::exit_code].
+fn main() {
 	env_logger::Builder::from_env(Env::default().default_filter_or("warn")).init();
-	color_eyre::install()?;
 
-	let opts: Opts = Opts::parse();
+	// Invalid arguments exit with the usage code rather than the code of clap
+	let opts = Opts::try_parse().unwrap_or_else(|e| {
+		if !e.use_stderr() {
+			e.exit();
+		}
+		let _ = e.print();
+		std::process::exit(exitcode::USAGE);
+	});
 	log::debug!("opts: {opts:#?}");
 
+	if let Err(e) = run(opts) {
+		eprintln!("error: {e}");
+		if let PRdocLibError::ValidationErrors(diagnostics) = &e {
+			diagnostics.iter().for_each(|d| eprintln!("{d}"));
+		}
+		std::process::exit(e.exit_code());
+	}
+}
+
+/// Run the command selected by the `opts`
+fn run(opts: Opts) -> Result<(), PRdocLibError> {
 	let config = match Config::load(opts.config) {
 		Ok(c) => {
 			log::debug!("Config found: {:#?}", c);
 			c
 		},
-		Err(_e) => {
+		Err(PRdocLibError::MissingConfig | PRdocLibError::ProjectRootNotFound(_)) => {
 			log::warn!("No config could be found, using default");
 			Config::get_default_config()
 		},
+		Err(e) => return Err(e),
 	};
 
 	let prdoc_dir: Vec<PathBuf> = match (&config.prdoc_folders, opts.prdoc_folders) {
@@ -55,77 +94,137 @@ fn main() -> color_eyre::Result<()> {
 			let template_path = prdoclib::utils::get_template_path(&config);
 
 			log::debug!("PRDoc folder: {dir:?}");
-			match GenerateCmd::run(
-				cmd_opts.dry_run,
-				cmd_opts.number,
-				None,
-				Some(dir),
-				template_path,
-			) {
-				Ok(_) => Ok(()),
-				Err(e) => {
-					log::error!("{e}");
-					std::process::exit(exitcode::IOERR);
-				},
-			}
+			GenerateCmd::run(cmd_opts.dry_run, cmd_opts.number, None, Some(dir), template_path)
 		},
 
 		Some(SubCommand::Check(cmd_opts)) => {
 			log::debug!("cmd_opts: {cmd_opts:#?}");
-			let mut results: Vec<CheckResult> = prdoc_dir
-				.iter()
-				.flat_map(|dir| {
-					CheckCmd::run(
-						&config,
-						cmd_opts.schema.clone(),
-						dir,
-						cmd_opts.file.clone(),
-						cmd_opts.number.clone(),
-						cmd_opts.list.clone(),
-					)
-					.unwrap()
-				})
-				.collect();
+			let fix = match (cmd_opts.fix, cmd_opts.dry_run) {
+				(false, _) => None,
+				(true, false) => Some(FixMode::Apply),
+				(true, true) => Some(FixMode::DryRun),
+			};
+			let schema = schema_from_cli(cmd_opts.schema.clone());
+			let changes = match &cmd_opts.changed_since {
+				Some(rev) => Some(ChangedPRDocs::find(
+					&Git::new(env::current_dir()?),
+					rev,
+					cmd_opts.staged,
+					&prdoc_dir,
+				)?),
+				None => None,
+			};
+			let config = config.with_jobs(cmd_opts.jobs);
+			let results: Vec<CheckReport> = match &changes {
+				Some(changes) => CheckCmd::run_changed(&config, schema, changes, &prdoc_dir, fix),
+				None => CheckCmd::run(
+					&config,
+					schema,
+					&prdoc_dir,
+					cmd_opts.file.clone(),
+					cmd_opts.number.clone(),
+					cmd_opts.list.clone(),
+					fix,
+				),
+			}?
+			.into_iter()
+			.map(|report| if cmd_opts.deny_warnings { report.deny_warnings() } else { report })
+			.collect();
 
-			results.sort_by(|a, b| match (&a.0, &b.0) {
-				(File(path_a), File(path_b)) => path_a.cmp(path_b),
-				(PRDocSource::Number(num_a), PRDocSource::Number(num_b)) |
-				(PRDocSource::Both(_, num_a), PRDocSource::Both(_, num_b)) => num_a.cmp(num_b),
-				_ => Ordering::Greater,
-			});
-
-			if !opts.json {
-				for (src, result) in &results {
-					if *result {
-						continue;
+			let format = match cmd_opts.format {
+				_ if opts.json => OutputFormat::Json,
+				Some(format) => format,
+				None if env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true") =>
+					OutputFormat::Github,
+				None => OutputFormat::Text,
+			};
+			if matches!(format, OutputFormat::Text | OutputFormat::Github) {
+				for fix in results.iter().filter_map(|report| report.fix.as_ref()) {
+					let action = if fix.written { "Fixed" } else { "Would fix" };
+					println!("{action} {}:", fix.file.display());
+					for change in &fix.changes {
+						println!("  - {change}");
+					}
+					if cmd_opts.dry_run {
+						print!("{}", fix.diff());
 					}
-
-					let pr_number: PRNumber = src.into();
-					println!("PR #{pr_number: <4} -> ERR");
 				}
+			}
 
-				let plural_s = if results.len() > 1 { "s" } else { "" };
-				println!("Checked {} file{plural_s}.", results.len());
-			} else {
-				let json =
-					serde_json::to_string_pretty(&results).expect("We can serialize the result");
-				println!("{json}");
+			match format {
+				OutputFormat::Text => {
+					for report in &results {
+						let pr_number = report.number().unwrap_or_default();
+						match (report.status, report.error) {
+							(_, Some(error)) => println!("PR #{pr_number: <4} -> ERR ({error})"),
+							(CheckStatus::Warning, None) => println!("PR #{pr_number: <4} -> WARN"),
+							_ => {},
+						}
+						for message in &report.messages {
+							eprintln!("error: {message}");
+						}
+						for diagnostic in &report.diagnostics {
+							eprintln!("{diagnostic}");
+						}
+					}
+
+					let plural_s = if results.len() > 1 { "s" } else { "" };
+					let count = |severity| {
+						results
+							.iter()
+							.flat_map(|report| &report.diagnostics)
+							.filter(|d| d.severity == severity)
+							.count()
+					};
+					println!(
+						"Checked {} file{plural_s}: {} error(s), {} warning(s).",
+						results.len(),
+						count(Severity::Error) +
+							results.iter().map(|report| report.messages.len()).sum::<usize>(),
+						count(Severity::Warning)
+					);
+				},
+				OutputFormat::Json | OutputFormat::Sarif => {
+					let report = if format == OutputFormat::Json {
+						report::json(&results)
+					} else {
+						report::sarif(&results)
+					};
+					println!("{}", serde_json::to_string_pretty(&report)?);
+				},
+				OutputFormat::Junit => print!("{}", report::junit(&results)),
+				OutputFormat::Github => {
+					print!("{}", report::github(&results));
+					let plural_s = if results.len() > 1 { "s" } else { "" };
+					println!("Checked {} file{plural_s}.", results.len());
+				},
 			}
 
-			let all_good = results.iter().map(|(_number, res)| res).all(|&res| res);
-			if all_good {
+			// The machine readable reports only cover the checked files and keep stdout for
+			// themselves
+			for deleted in changes.iter().flat_map(|changes| &changes.deleted) {
+				match format {
+					OutputFormat::Text | OutputFormat::Github =>
+						println!("Deleted {}", deleted.display()),
+					_ => eprintln!("Deleted {}", deleted.display()),
+				}
+			}
+
+			let mut invalid = results.iter().filter(|report| !report.is_valid()).peekable();
+			if invalid.peek().is_none() {
 				std::process::exit(exitcode::OK)
+			} else if invalid.all(|report| report.error == Some(CheckErrorKind::NotFound)) {
+				std::process::exit(exitcode::NOINPUT)
 			} else {
 				std::process::exit(exitcode::DATAERR)
 			}
 		},
 
 		Some(SubCommand::Scan(cmd_opts)) => {
-			let schema_path = config.schema_path();
-			let schema = Schema::new(schema_path);
+			let schema = config.load_schema(None, &get_project_root()?)?;
 
 			log::debug!("cmd_opts: {cmd_opts:#?}");
-			let files = ScanCmd::run(schema.clone(), prdoc_dir, cmd_opts.all);
+			let files = ScanCmd::run(schema.clone(), prdoc_dir, cmd_opts.all)?;
 			let load_cmd = LoadCmd::new(schema);
 
 			let mut res: Vec<(Option<PRNumber>, PathBuf)> = files
@@ -146,7 +245,7 @@ fn main() -> color_eyre::Result<()> {
 			}
 
 			if opts.json {
-				println!("{}", serde_json::to_string_pretty(&res).unwrap());
+				println!("{}", serde_json::to_string_pretty(&res)?);
 			} else {
 				res.iter().for_each(|(n, f)| {
 					println!(
@@ -165,33 +264,264 @@ fn main() -> color_eyre::Result<()> {
 
 		Some(SubCommand::Load(cmd_opts)) => {
 			log::debug!("cmd_opts: {cmd_opts:#?}");
+			let config = config.with_jobs(cmd_opts.jobs);
+			let selector = cmd_opts.select.as_deref().map(Selector::parse).transpose()?;
+
+			let format = if opts.json { LoadFormat::Json } else { cmd_opts.format };
+
+			// The PRDoc are loaded sorted by number so they can be written as soon as loaded
+			if format == LoadFormat::Ndjson &&
+				cmd_opts.sort_by == SortBy::Number &&
+				!cmd_opts.reverse
+			{
+				let load_cmd =
+					LoadCmd::from_config(&config, schema_from_cli(cmd_opts.schema.clone()))?;
+				let mut out = std::io::stdout().lock();
+				let status = load_cmd.for_each(
+					&prdoc_dir,
+					cmd_opts.file.clone(),
+					cmd_opts.number.clone(),
+					cmd_opts.list.clone(),
+					&cmd_opts.filter(),
+					|wrapper| {
+						let wrappers = std::slice::from_ref(&wrapper);
+						match &selector {
+							Some(selector) =>
+								export::ndjson(&selector.select_all(wrappers)?, &mut out),
+							None => export::ndjson(wrappers, &mut out),
+						}
+					},
+				)?;
+				std::process::exit(if status { exitcode::OK } else { exitcode::DATAERR });
+			}
+
+			let mut result = LoadCmd::run(
+				&config,
+				schema_from_cli(cmd_opts.schema.clone()),
+				&prdoc_dir,
+				cmd_opts.file.clone(),
+				cmd_opts.number.clone(),
+				cmd_opts.list.clone(),
+				&cmd_opts.filter(),
+			)?;
+			LoadCmd::sort(&mut result.1, cmd_opts.sort_by, cmd_opts.reverse);
+
+			if let Some(selector) = selector {
+				let values = selector.select_all(&result.1)?;
+				if format == LoadFormat::Json {
+					println!("{}", serde_json::to_string_pretty(&values)?);
+				} else {
+					for value in values {
+						println!("{}", serde_json::to_string(&value)?);
+					}
+				}
+			} else {
+				match format {
+					LoadFormat::Yaml => println!("{}", serde_yaml::to_string(&result.1)?),
+					LoadFormat::Json => println!("{}", serde_json::to_string_pretty(&result.1)?),
+					LoadFormat::Ndjson => export::ndjson(&result.1, std::io::stdout().lock())?,
+					LoadFormat::Toml => print!("{}", export::toml(&result.1)?),
+					LoadFormat::Csv => print!("{}", export::csv(&result.1)),
+				}
+			}
+			if result.0 {
+				std::process::exit(exitcode::OK);
+			} else {
+				std::process::exit(exitcode::DATAERR)
+			}
+		},
+
+		Some(SubCommand::Schema(cmd_opts)) => {
+			log::debug!("cmd_opts: {cmd_opts:#?}");
+
+			match cmd_opts.subcmd {
+				SchemaSubCommand::List => {
+					let schemas = SchemaCmd::list();
+					if opts.json {
+						println!("{}", serde_json::to_string_pretty(&schemas)?);
+					} else {
+						for schema in schemas {
+							println!(
+								"{}\t{}\t{}{}",
+								schema.name,
+								schema.version.unwrap_or_default(),
+								schema.title.unwrap_or_default(),
+								if schema.latest { " (latest)" } else { "" }
+							);
+						}
+					}
+				},
+				SchemaSubCommand::Export(export_opts) => {
+					let content = SchemaCmd::export(export_opts.version.as_deref())?;
+
+					if let Some(output) = export_opts.output {
+						std::fs::write(output, content)?;
+					} else {
+						println!("{content}");
+					}
+				},
+			}
+			Ok(())
+		},
+
+		Some(SubCommand::Migrate(cmd_opts)) => {
+			log::debug!("cmd_opts: {cmd_opts:#?}");
 
-			let result = prdoc_dir
+			let results: Vec<_> = prdoc_dir
 				.iter()
 				.map(|dir| {
-					LoadCmd::run(
+					MigrateCmd::run(
 						&config,
-						cmd_opts.schema.clone(),
+						schema_from_cli(cmd_opts.schema.clone()),
 						dir,
 						cmd_opts.file.clone(),
-						cmd_opts.number.clone(),
-						cmd_opts.list.clone(),
+						cmd_opts.dry_run,
 					)
-					.unwrap()
 				})
-				.fold((true, HashSet::new()), |(acc_status, acc_wrapper), (status, wrapper)| {
-					let mut new_wrapper = acc_wrapper;
-					new_wrapper.extend(wrapper);
-					(acc_status && status, new_wrapper)
-				});
+				.collect::<Result<Vec<_>, _>>()?
+				.into_iter()
+				.flatten()
+				.collect();
+
+			let mut all_good = true;
+			let mut json = vec![];
+			for (file, result) in &results {
+				match result {
+					Ok(result) if opts.json => json.push(serde_json::to_value(result)?),
+					Ok(result) if result.changed && cmd_opts.dry_run => print!("{}", result.diff()),
+					Ok(result) if result.changed => println!(
+						"Migrated {} to {}",
+						file.display(),
+						result.to.map(|v| v.to_string()).unwrap_or_default()
+					),
+					Ok(_) => log::debug!("{} is up to date", file.display()),
+					Err(e) => {
+						all_good = false;
+						if opts.json {
+							json.push(serde_json::json!({ "file": file, "error": e.to_string() }));
+						} else {
+							eprintln!("Failed migrating {}: {e}", file.display());
+						}
+						if let PRdocLibError::ValidationErrors(diagnostics) = e {
+							diagnostics.iter().for_each(|d| eprintln!("{d}"));
+						}
+					},
+				}
+			}
 
 			if opts.json {
-				println!("{}", serde_json::to_string_pretty(&result.1).unwrap());
+				println!("{}", serde_json::to_string_pretty(&json)?);
 			} else {
-				println!("{}", serde_yaml::to_string(&result.1).unwrap());
+				let changed = results.iter().filter(|(_, r)| r.as_ref().is_ok_and(|r| r.changed));
+				let plural_s = if results.len() > 1 { "s" } else { "" };
+				println!(
+					"{} {} of {} file{plural_s}.",
+					if cmd_opts.dry_run { "Would migrate" } else { "Migrated" },
+					changed.count(),
+					results.len()
+				);
 			}
-			if result.0 {
-				std::process::exit(exitcode::OK);
+
+			if all_good {
+				std::process::exit(exitcode::OK)
+			} else {
+				std::process::exit(exitcode::DATAERR)
+			}
+		},
+
+		Some(SubCommand::SemverCheck(cmd_opts)) => {
+			log::debug!("cmd_opts: {cmd_opts:#?}");
+
+			let (status, prdocs) = LoadCmd::run(
+				&config,
+				schema_from_cli(cmd_opts.schema.clone()),
+				&prdoc_dir,
+				cmd_opts.file.clone(),
+				cmd_opts.number.clone(),
+				cmd_opts.list.clone(),
+				&Filter::default(),
+			)?;
+			// The bumps declared by the prdocs that could not be loaded are unknown
+			if !status {
+				log::error!("Some prdocs could not be loaded, their bumps are not compared");
+			}
+			for wrapper in prdocs.iter().filter(|wrapper| wrapper.prdoc.is_none()) {
+				log::error!(
+					"{} could not be loaded, its bumps are not compared",
+					wrapper.file.display()
+				);
+			}
+			let status = status && prdocs.iter().all(|wrapper| wrapper.prdoc.is_some());
+
+			let repo = match cmd_opts.repo {
+				Some(repo) => repo,
+				None => get_project_root()?,
+			};
+			let report =
+				SemverCheckCmd::new(Git::new(repo)).check(&cmd_opts.from, &cmd_opts.to, &prdocs)?;
+
+			if opts.json {
+				println!("{}", serde_json::to_string_pretty(&report)?);
+			} else {
+				let version = |v: &Option<semver::Version>| {
+					v.as_ref().map(|v| v.to_string()).unwrap_or_else(|| "-".into())
+				};
+				let level = |l: Option<_>| l.map(|l: prdoclib::model::SemverLevel| l.to_string());
+
+				for krate in &report.crates {
+					println!(
+						"{}\t{} -> {}\tactual: {}\tdeclared: {}{}",
+						krate.name,
+						version(&krate.from),
+						version(&krate.to),
+						level(krate.actual).unwrap_or_else(|| "n/a".into()),
+						level(krate.declared).unwrap_or_else(|| "none".into()),
+						match krate.issue {
+							Some(SemverIssue::Underbumped) => "\tERR: bumped less than declared",
+							Some(SemverIssue::Undeclared) => "\tERR: bump not declared",
+							None => "",
+						}
+					);
+				}
+				let issues = report.issues().count();
+				println!(
+					"Checked {} crate(s) between {} and {}: {issues} issue(s).",
+					report.crates.len(),
+					report.from,
+					report.to
+				);
+			}
+
+			if status && report.issues().next().is_none() {
+				std::process::exit(exitcode::OK)
+			} else {
+				std::process::exit(exitcode::DATAERR)
+			}
+		},
+
+		Some(SubCommand::Summary(cmd_opts)) => {
+			log::debug!("cmd_opts: {cmd_opts:#?}");
+
+			let (status, wrappers) = LoadCmd::run(
+				&config,
+				schema_from_cli(cmd_opts.schema.clone()),
+				&prdoc_dir,
+				None,
+				cmd_opts.number.clone(),
+				cmd_opts.list.clone(),
+				&Filter::default(),
+			)?;
+			let summary = SummaryCmd::summarize(&wrappers);
+
+			let format = if opts.json { SummaryFormat::Json } else { cmd_opts.format };
+			match format {
+				SummaryFormat::Text => print!("{}", summary.text()),
+				SummaryFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+				SummaryFormat::Markdown => print!("{}", summary.markdown()),
+			}
+
+			if status {
+				std::process::exit(exitcode::OK)
 			} else {
 				std::process::exit(exitcode::DATAERR)
 			}
@@ -204,7 +534,8 @@ fn main() -> color_eyre::Result<()> {
 				VersionCmd::run(name, version, opts.json);
 				Ok(())
 			} else {
-				unreachable!("We show help if there is no arg");
+				Opts::command().print_help()?;
+				std::process::exit(exitcode::USAGE);
 			},
 	}
 }
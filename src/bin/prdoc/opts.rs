@@ -2,8 +2,15 @@
 //! supported by the cli.
 #![warn(missing_docs)]
 
-use clap::{crate_authors, crate_version, ColorChoice, Parser, Subcommand};
-use prdoclib::{common::PRNumber, config};
+use clap::{crate_authors, crate_version, ColorChoice, Parser, Subcommand, ValueEnum};
+use prdoclib::{
+	commands::load::SortBy,
+	common::PRNumber,
+	config,
+	filter::Filter,
+	model::{Audience, SemverLevel},
+	schema::SchemaSource,
+};
 use std::path::PathBuf;
 
 /// prdoc is a utility to generate, check and load PRDoc files.
@@ -49,6 +56,22 @@ pub enum SubCommand {
 	#[allow(missing_docs)]
 	#[clap(version = crate_version!(), author = crate_authors!())]
 	Load(LoadOpts),
+
+	#[allow(missing_docs)]
+	#[clap(version = crate_version!(), author = crate_authors!())]
+	Schema(SchemaOpts),
+
+	#[allow(missing_docs)]
+	#[clap(version = crate_version!(), author = crate_authors!())]
+	Migrate(MigrateOpts),
+
+	#[allow(missing_docs)]
+	#[clap(version = crate_version!(), author = crate_authors!())]
+	SemverCheck(SemverCheckOpts),
+
+	#[allow(missing_docs)]
+	#[clap(version = crate_version!(), author = crate_authors!())]
+	Summary(SummaryOpts),
 }
 /// Generate a new file. It will be saved by default unless you provide --dry-run.
 /// The command will fail if the target file already exists.
@@ -84,9 +107,62 @@ pub struct CheckOpts {
 	#[clap(short, long, conflicts_with_all = ["file", "number"])]
 	pub list: Option<PathBuf>,
 
-	/// Schema to be used. Passing this flag/ENV overrides the value from the config.
+	/// Only check the prdoc files added or modified since this git revision, ie `origin/master`.
+	/// The files deleted since then are listed separately.
+	#[clap(long, value_name = "REV", conflicts_with_all = ["file", "number", "list"])]
+	pub changed_since: Option<String>,
+
+	/// Compare the revision passed to `--changed-since` with the index instead of `HEAD`
+	#[clap(long, requires = "changed_since")]
+	pub staged: bool,
+
+	/// Schema to be used, either a path or an embedded schema such as `builtin:v1`.
+	/// Passing this flag/ENV overrides the value from the config.
 	#[clap(short, long, env = "PRDOC_SCHEMA")]
-	pub schema: Option<PathBuf>,
+	pub schema: Option<SchemaSource>,
+
+	/// Apply the safe fixes, such as adding the missing required arrays or renaming files with
+	/// an invalid name, then check the fixed files
+	#[clap(long)]
+	pub fix: bool,
+
+	/// Do not rewrite the files, show the fixes as a diff instead
+	#[clap(long, requires = "fix")]
+	pub dry_run: bool,
+
+	/// Fail when warnings are reported, such as deprecated properties or lints configured as
+	/// warnings
+	#[clap(long)]
+	pub deny_warnings: bool,
+
+	/// Number of files validated in parallel. Overrides the `jobs` of the config, which defaults
+	/// to the number of CPUs. The output does not depend on it.
+	#[clap(long)]
+	pub jobs: Option<usize>,
+
+	/// Format of the report. `--json` is a shortcut for `--format json`.
+	/// Defaults to `github` when running in GitHub Actions, `text` otherwise.
+	#[clap(long, value_enum)]
+	pub format: Option<OutputFormat>,
+}
+
+/// Format of the report of the check command
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+	/// Human readable output
+	Text,
+
+	/// A JSON entry per checked file with its diagnostics
+	Json,
+
+	/// SARIF 2.1.0, as used by GitHub code scanning
+	Sarif,
+
+	/// JUnit XML, as used by the test views of Jenkins or GitLab
+	Junit,
+
+	/// GitHub Actions annotations, shown inline on the diff of the PR
+	Github,
 }
 
 /// Scan a directory for prdoc files based on their name
@@ -118,7 +194,211 @@ pub struct LoadOpts {
 	#[clap(short, long, conflicts_with_all = ["file", "number"])]
 	pub list: Option<PathBuf>,
 
-	/// Schema to be used. Passing this flag/ENV overrides the value from the config.
+	/// Schema to be used, either a path or an embedded schema such as `builtin:v1`.
+	/// Passing this flag/ENV overrides the value from the config.
 	#[clap(short, long, env = "PRDOC_SCHEMA")]
-	pub schema: Option<PathBuf>,
+	pub schema: Option<SchemaSource>,
+
+	/// Number of files validated in parallel. Overrides the `jobs` of the config, which defaults
+	/// to the number of CPUs. The output does not depend on it.
+	#[clap(long)]
+	pub jobs: Option<usize>,
+
+	/// Format of the output. `--json` is a shortcut for `--format json`.
+	#[clap(long, value_enum, default_value_t = LoadFormat::Yaml)]
+	pub format: LoadFormat,
+
+	/// Order of the prdoc. The prdoc with the same key are sorted by number then path.
+	#[clap(long, value_enum, value_name = "KEY", default_value_t = SortBy::Number)]
+	pub sort_by: SortBy,
+
+	/// Reverse the order of the prdoc
+	#[clap(long)]
+	pub reverse: bool,
+
+	/// Only output the values selected by this expression, one JSON value per line or as a JSON
+	/// array with `--format json`. For instance `.content.doc[?audience == "Runtime
+	/// User"].description`. See the documentation of the load command for the syntax.
+	#[clap(long, value_name = "EXPR")]
+	pub select: Option<String>,
+
+	/// Only load the prdoc documented for this audience, ie `Runtime User`
+	#[clap(long, help_heading = "Filters")]
+	pub audience: Vec<Audience>,
+
+	/// Only load the prdoc listing this crate
+	#[clap(long = "crate", value_name = "CRATE", help_heading = "Filters")]
+	pub crates: Vec<String>,
+
+	/// Only load the prdoc with a crate bumped at this level, ie `major`. Combined with `--crate`,
+	/// the given crates must be bumped at this level.
+	#[clap(long, help_heading = "Filters")]
+	pub semver: Option<SemverLevel>,
+
+	/// Only load the prdoc with this tag
+	#[clap(long, help_heading = "Filters")]
+	pub tag: Vec<String>,
+
+	/// Only load the prdoc with an author containing this value
+	#[clap(long, help_heading = "Filters")]
+	pub author: Vec<String>,
+
+	/// Only load the prdoc with a database or runtime migration
+	#[clap(long, help_heading = "Filters")]
+	pub has_migrations: bool,
+
+	/// Only load the prdoc involving host functions
+	#[clap(long, help_heading = "Filters")]
+	pub has_host_functions: bool,
+}
+
+/// Format of the output of the load command
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadFormat {
+	/// A YAML sequence of all the prdoc
+	Yaml,
+
+	/// A JSON array of all the prdoc
+	Json,
+
+	/// One JSON object per line and per prdoc
+	Ndjson,
+
+	/// An array of `prdoc` tables
+	Toml,
+
+	/// A row per audience and per crate of each prdoc, for spreadsheets
+	Csv,
+}
+
+impl LoadOpts {
+	/// The filter selecting the prdoc to load. The filters can be repeated and must all match.
+	pub fn filter(&self) -> Filter {
+		Filter {
+			audiences: self.audience.clone(),
+			crates: self.crates.clone(),
+			semver: self.semver,
+			tags: self.tag.clone(),
+			authors: self.author.clone(),
+			has_migrations: self.has_migrations,
+			has_host_functions: self.has_host_functions,
+		}
+	}
+}
+
+/// List and export the schemas embedded in the cli
+#[derive(Parser, Debug)]
+pub struct SchemaOpts {
+	#[allow(missing_docs)]
+	#[clap(subcommand)]
+	pub subcmd: SchemaSubCommand,
+}
+
+/// Define the list of the schema sub-commands.
+#[derive(Subcommand, Debug)]
+pub enum SchemaSubCommand {
+	/// List the embedded schemas
+	List,
+
+	/// Export one of the embedded schemas
+	Export(SchemaExportOpts),
+}
+
+/// Export one of the embedded schemas
+#[derive(Parser, Debug)]
+pub struct SchemaExportOpts {
+	/// Version of the schema, ie `v1` or `builtin:v1`. Defaults to the latest version.
+	#[clap(index = 1)]
+	pub version: Option<String>,
+
+	/// Write the schema to this file instead of stdout
+	#[clap(short, long)]
+	pub output: Option<PathBuf>,
+}
+
+/// Upgrade prdoc files declaring an older version of the schema to the current one.
+/// The files are rewritten in place unless you provide --dry-run.
+#[derive(Parser, Debug)]
+pub struct MigrateOpts {
+	/// Only migrate this file. It can be relative to the base directory.
+	#[clap(short, long)]
+	pub file: Option<PathBuf>,
+
+	/// Do not rewrite the files, show the changes as a diff instead
+	#[clap(long)]
+	pub dry_run: bool,
+
+	/// Schema to migrate to, either a path or an embedded schema such as `builtin:v1`.
+	/// Passing this flag/ENV overrides the value from the config.
+	#[clap(short, long, env = "PRDOC_SCHEMA")]
+	pub schema: Option<SchemaSource>,
+}
+
+/// Compare the semver bumps declared in prdoc files with the versions of the crates in the
+/// Cargo.toml files at two git revisions.
+#[derive(Parser, Debug)]
+pub struct SemverCheckOpts {
+	/// Base revision, ie the tag of the last release
+	#[clap(index = 1)]
+	pub from: String,
+
+	/// Revision to compare with the base revision
+	#[clap(index = 2, default_value = "HEAD")]
+	pub to: String,
+
+	/// Path of the git repository. Defaults to the root of the project.
+	#[clap(long)]
+	pub repo: Option<PathBuf>,
+
+	/// Only consider this prdoc file
+	#[clap(short, long, conflicts_with = "number")]
+	pub file: Option<PathBuf>,
+
+	/// Only consider the prdoc files of these PR numbers
+	#[clap(short, long)]
+	pub number: Option<Vec<PRNumber>>,
+
+	/// Get the list of PR numbers from a file
+	#[clap(short, long, conflicts_with_all = ["file", "number"])]
+	pub list: Option<PathBuf>,
+
+	/// Schema to be used, either a path or an embedded schema such as `builtin:v1`.
+	/// Passing this flag/ENV overrides the value from the config.
+	#[clap(short, long, env = "PRDOC_SCHEMA")]
+	pub schema: Option<SchemaSource>,
+}
+
+/// Summarize the semver bumps declared in prdoc files: the strongest level of each crate, the PRs
+/// causing it and the notes. The crates with a major bump come first.
+#[derive(Parser, Debug)]
+pub struct SummaryOpts {
+	/// Only consider the prdoc files of these PR numbers
+	#[clap(short, long)]
+	pub number: Option<Vec<PRNumber>>,
+
+	/// Get the list of PR numbers from a file
+	#[clap(short, long, conflicts_with = "number")]
+	pub list: Option<PathBuf>,
+
+	/// Schema to be used, either a path or an embedded schema such as `builtin:v1`.
+	/// Passing this flag/ENV overrides the value from the config.
+	#[clap(short, long, env = "PRDOC_SCHEMA")]
+	pub schema: Option<SchemaSource>,
+
+	/// Format of the summary. `--json` is a shortcut for `--format json`.
+	#[clap(long, value_enum, default_value_t = SummaryFormat::Text)]
+	pub format: SummaryFormat,
+}
+
+/// Format of the output of the summary command
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryFormat {
+	/// Human readable output
+	Text,
+
+	/// A JSON entry per crate
+	Json,
+
+	/// A Markdown table, ready to be pasted in the release notes
+	Markdown,
 }
@@ -4,201 +4,901 @@
 use crate::{
 	common::PRNumber,
 	config::PRDocConfig,
+	diagnostic::{Diagnostic, Severity},
 	doc_filename::DocFileName,
 	docfile::DocFile,
 	error::{self, PRdocLibError},
+	fix::{self, Fix, FIXES},
+	git::{FileStatus, Git},
+	lint::{Linter, LINT_PREFIX},
 	prdoc_source::PRDocSource,
-	schema::Schema,
-	utils::{get_numbers_from_file, get_project_root},
+	schema::{Schema, SchemaSource},
+	utils::{get_numbers_from_file, get_project_root, par_map, resolve_file},
 };
+use serde::Serialize;
+use similar::TextDiff;
 use std::{
-	collections::HashSet,
+	collections::{btree_map::Entry, BTreeMap, HashSet},
+	ffi::OsStr,
+	fmt::Display,
+	fs,
 	path::{Path, PathBuf},
 };
 
 /// Implementation of the main [check](/prdoc::opts::CheckOpts) command of the cli.
 pub struct CheckCmd {
 	pub(crate) schema: Schema,
+	pub(crate) linter: Linter,
+	pub(crate) fixes: Vec<Fix>,
+	pub(crate) fix: Option<FixMode>,
+	pub(crate) jobs: usize,
 }
 
-/// PRDoc are checked via a PR number or a file.
+/// How the check applies the [fixes](crate::fix) to the files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixMode {
+	/// Rewrite and rename the files
+	Apply,
+
+	/// Only report the changes, the files are checked as if they were fixed
+	DryRun,
+}
+
+/// Changes made to a file by the fixes
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct FixReport {
+	/// The file before the fixes
+	pub file: PathBuf,
+
+	/// The new path of the file, if it was renamed
+	pub renamed: Option<PathBuf>,
+
+	/// Description of the changes
+	pub changes: Vec<String>,
+
+	/// Whether the changes were written
+	pub written: bool,
+
+	#[serde(skip)]
+	original: String,
+
+	#[serde(skip)]
+	fixed: String,
+}
+
+impl FixReport {
+	/// Return a unified diff of the changes
+	pub fn diff(&self) -> String {
+		let from = self.file.display().to_string();
+		let to = self.renamed.as_ref().unwrap_or(&self.file).display().to_string();
+		TextDiff::from_lines(&self.original, &self.fixed)
+			.unified_diff()
+			.header(&from, &to)
+			.to_string()
+	}
+}
+
+/// Category of the problem that made a check fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckErrorKind {
+	/// No PRDoc file could be found for the PR number
+	NotFound,
+
+	/// The name of the file does not match `pr_<number>[_<title>].prdoc`
+	InvalidFilename,
+
+	/// Other PRDoc files exist for the same PR and they are not distinct parts
+	Duplicate,
+
+	/// The file could not be read
+	Io,
+
+	/// The file is not valid YAML
+	Syntax,
+
+	/// The schema required by the file is not available
+	MissingSchema,
+
+	/// The file does not comply with the schema
+	Schema,
+
+	/// At least one lint rule failed
+	Lint,
+
+	/// Warnings were reported while they are denied
+	Warnings,
+}
+
+impl CheckErrorKind {
+	/// Code of the diagnostics reporting this kind of error when no more specific one exists
+	pub fn code(&self) -> &'static str {
+		match self {
+			CheckErrorKind::NotFound => "prdoc/not-found",
+			CheckErrorKind::InvalidFilename => "prdoc/invalid-filename",
+			CheckErrorKind::Duplicate => "prdoc/duplicate",
+			CheckErrorKind::Io => "prdoc/io",
+			CheckErrorKind::Syntax => "yaml/syntax",
+			CheckErrorKind::MissingSchema => "schema/version",
+			CheckErrorKind::Schema => "schema/invalid",
+			CheckErrorKind::Lint => "lint/failed",
+			CheckErrorKind::Warnings => "prdoc/warnings",
+		}
+	}
+}
+
+impl Display for CheckErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			CheckErrorKind::NotFound => "not found",
+			CheckErrorKind::InvalidFilename => "invalid filename",
+			CheckErrorKind::Duplicate => "duplicate",
+			CheckErrorKind::Io => "read error",
+			CheckErrorKind::Syntax => "YAML syntax error",
+			CheckErrorKind::MissingSchema => "missing schema",
+			CheckErrorKind::Schema => "schema violation",
+			CheckErrorKind::Lint => "lint error",
+			CheckErrorKind::Warnings => "warnings denied",
+		})
+	}
+}
+
+/// Outcome of the check of a PRDoc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckStatus {
+	/// The PRDoc is valid
+	Valid,
+
+	/// The PRDoc is valid but some warnings were reported
+	Warning,
+
+	/// The PRDoc is not valid, see the `error` of the report
+	Invalid,
+}
+
+/// Result of the check of a single PRDoc.
+///
+/// PRDoc are checked via a PR number or a file:
 /// - When passing a file path, it may not result in a `PRNumber`.
 /// - When passing a PRNumber, it also may not result in a file
-pub type CheckResult = (PRDocSource, bool);
+///
+/// The diagnostics explain why a file is not valid, either because it does not comply with the
+/// schema or because a lint failed. Valid files may also come with warnings. The problems that
+/// cannot be located in the file, such as a missing file, are only described in the `messages`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct CheckReport {
+	/// What was requested to be checked
+	pub source: PRDocSource,
+
+	/// The path of the file that was checked, if one was found
+	pub file: Option<PathBuf>,
+
+	/// Whether the file is valid
+	pub status: CheckStatus,
+
+	/// The main reason why the file is not valid
+	pub error: Option<CheckErrorKind>,
+
+	/// Descriptions of the problems that have no diagnostic
+	pub messages: Vec<String>,
+
+	/// The problems found in the file
+	pub diagnostics: Vec<Diagnostic>,
+
+	/// The changes made by the fixes, if they were enabled and something was fixed
+	pub fix: Option<FixReport>,
+}
+
+impl CheckReport {
+	/// Create the report of a file from its diagnostics. The error kind is derived from the
+	/// diagnostics.
+	pub fn new(source: PRDocSource, file: PathBuf, diagnostics: Vec<Diagnostic>) -> Self {
+		let mut report = Self {
+			source,
+			file: Some(file),
+			status: CheckStatus::Valid,
+			error: None,
+			messages: vec![],
+			diagnostics: vec![],
+			fix: None,
+		};
+		for diagnostic in diagnostics {
+			report = report.with_diagnostic(diagnostic);
+		}
+		report
+	}
+
+	/// Add a diagnostic to the report and update its status
+	pub fn with_diagnostic(mut self, diagnostic: Diagnostic) -> Self {
+		if diagnostic.severity == Severity::Error {
+			let kind = match diagnostic.code.as_str() {
+				code if code.starts_with(LINT_PREFIX) => CheckErrorKind::Lint,
+				code => [
+					CheckErrorKind::InvalidFilename,
+					CheckErrorKind::Duplicate,
+					CheckErrorKind::Syntax,
+					CheckErrorKind::MissingSchema,
+				]
+				.into_iter()
+				.find(|kind| kind.code() == code)
+				.unwrap_or(CheckErrorKind::Schema),
+			};
+			// Lint errors matter less than the others as lints only run on valid files
+			self.error = match self.error {
+				Some(error) if error != CheckErrorKind::Lint => Some(error),
+				_ => Some(kind),
+			};
+			self.status = CheckStatus::Invalid;
+		} else if self.status == CheckStatus::Valid {
+			self.status = CheckStatus::Warning;
+		}
+		self.diagnostics.push(diagnostic);
+		self
+	}
+
+	/// Create the report of a PRDoc that could not be checked
+	pub fn failed(
+		source: PRDocSource,
+		file: Option<PathBuf>,
+		error: CheckErrorKind,
+		message: impl Into<String>,
+	) -> Self {
+		Self {
+			source,
+			file,
+			status: CheckStatus::Invalid,
+			error: Some(error),
+			messages: vec![message.into()],
+			diagnostics: vec![],
+			fix: None,
+		}
+	}
+
+	/// Make the check fail if warnings were reported. The diagnostics keep their severity.
+	pub fn deny_warnings(mut self) -> Self {
+		if self.status == CheckStatus::Warning {
+			self.status = CheckStatus::Invalid;
+			self.error = Some(CheckErrorKind::Warnings);
+		}
+		self
+	}
+
+	/// Return true if the PRDoc is valid, possibly with warnings
+	pub fn is_valid(&self) -> bool {
+		self.status != CheckStatus::Invalid
+	}
+
+	/// Return the PR number of the PRDoc, if known
+	pub fn number(&self) -> Option<PRNumber> {
+		match &self.source {
+			PRDocSource::Number(number) | PRDocSource::Both(_, number) => Some(*number),
+			PRDocSource::File(_) =>
+				self.file.as_ref().and_then(|f| DocFileName::try_from(f).ok()).map(|f| f.number),
+		}
+	}
+}
+
+/// The PRDoc files changed since a git revision, as listed by [ChangedPRDocs::find]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangedPRDocs {
+	/// PRDoc files added or modified, they are checked
+	pub changed: Vec<PathBuf>,
+
+	/// PRDoc files deleted, they cannot be checked anymore
+	pub deleted: Vec<PathBuf>,
+}
+
+impl ChangedPRDocs {
+	/// List the `.prdoc` files located in one of the `dirs` and changed between `rev` and `HEAD`,
+	/// or the index when `staged` is set. The paths are returned under the matching directory of
+	/// `dirs`. Only the local repository is read, nothing is fetched.
+	pub fn find(git: &Git, rev: &str, staged: bool, dirs: &[PathBuf]) -> error::Result<Self> {
+		let root = git.root()?.canonicalize()?;
+		let dirs: Vec<(&PathBuf, PathBuf)> =
+			dirs.iter().filter_map(|dir| Some((dir, dir.canonicalize().ok()?))).collect();
+
+		let mut changes = Self::default();
+		for (status, path) in git.diff_files(&git.rev_parse(rev)?, staged)? {
+			if path.extension() != Some(OsStr::new("prdoc")) {
+				continue;
+			}
+
+			let path = root.join(path);
+			let Some(file) = dirs.iter().find_map(|(dir, canonical)| {
+				path.strip_prefix(canonical).ok().map(|relative| dir.join(relative))
+			}) else {
+				continue;
+			};
+
+			match status {
+				FileStatus::Added | FileStatus::Modified => changes.changed.push(file),
+				FileStatus::Deleted => changes.deleted.push(file),
+			}
+		}
+		Ok(changes)
+	}
+}
 
 impl CheckCmd {
 	/// Create a new instance of the check command
 	pub fn new(schema: Schema) -> Self {
-		Self { schema }
+		Self { schema, linter: Linter::default(), fixes: FIXES.to_vec(), fix: None, jobs: 1 }
+	}
+
+	/// Use a custom linter instead of the default one
+	pub fn with_linter(mut self, linter: Linter) -> Self {
+		self.linter = linter;
+		self
+	}
+
+	/// Apply the fixes to the files before checking them
+	pub fn with_fix(mut self, fix: Option<FixMode>) -> Self {
+		self.fix = fix;
+		self
+	}
+
+	/// Check up to `jobs` files in parallel. The reports keep the order of the files.
+	pub fn with_jobs(mut self, jobs: usize) -> Self {
+		self.jobs = jobs;
+		self
+	}
+
+	/// Check the `files` in parallel, see [with_jobs](Self::with_jobs)
+	fn check_all<T: Sync>(
+		&self,
+		files: &[T],
+		check: impl Fn(&T) -> CheckReport + Sync,
+	) -> Vec<CheckReport> {
+		// Renaming files concurrently could lead two files to claim the same name
+		let jobs = if self.fix == Some(FixMode::Apply) { 1 } else { self.jobs };
+		par_map(files, jobs, check)
+	}
+
+	/// Apply the fixes to the `content` of `file`. `None` is returned if nothing was fixed.
+	fn fix(&self, mode: FixMode, file: &Path, content: &str) -> Option<FixReport> {
+		let (fixed, mut changes) = fix::apply(&self.fixes, content)
+			.map_err(|e| log::debug!("{} cannot be fixed: {e}", file.display()))
+			.ok()?;
+
+		let renamed = fix::canonical_filename(file).filter(|renamed| {
+			let exists = renamed.exists();
+			if exists {
+				log::warn!("{} cannot be renamed, {} exists", file.display(), renamed.display());
+			}
+			!exists
+		});
+		if let Some(renamed) = &renamed {
+			let name = renamed.file_name().unwrap_or_default().to_string_lossy();
+			changes.push(format!("Renamed the file to {name}"));
+		}
+		if changes.is_empty() {
+			return None;
+		}
+
+		let mut report = FixReport {
+			file: file.to_path_buf(),
+			renamed,
+			changes,
+			written: false,
+			original: content.to_string(),
+			fixed,
+		};
+
+		if mode == FixMode::Apply {
+			let target = report.renamed.as_deref().unwrap_or(file);
+			let written = fs::write(target, &report.fixed).and_then(|_| match target == file {
+				true => Ok(()),
+				false => fs::remove_file(file),
+			});
+			if let Err(e) = written {
+				log::error!("Failed writing the fixes of {}: {e}", file.display());
+				return None;
+			}
+			report.written = true;
+		}
+		Some(report)
+	}
+
+	/// Validate a file against the schema then run the lints on valid files.
+	/// When the fixes are enabled, the fixed content is validated.
+	fn validate(&self, source: PRDocSource, file: &Path) -> CheckReport {
+		let mut content = match fs::read_to_string(file) {
+			Ok(content) => content,
+			Err(e) => {
+				let kind = match e.kind() {
+					std::io::ErrorKind::NotFound => CheckErrorKind::NotFound,
+					_ => CheckErrorKind::Io,
+				};
+				return CheckReport::failed(source, Some(file.into()), kind, e.to_string());
+			},
+		};
+
+		let fix = self.fix.and_then(|mode| self.fix(mode, file, &content));
+		let file = match &fix {
+			Some(fix) => {
+				content = fix.fixed.clone();
+				fix.renamed.clone().unwrap_or_else(|| file.to_path_buf())
+			},
+			None => file.to_path_buf(),
+		};
+		let file = file.as_path();
+
+		let diagnostics = match self.schema.validate_str(file, &content) {
+			Ok(mut validated) => {
+				validated.diagnostics.extend(self.linter.lint(file, &content, &validated.prdoc));
+				validated.diagnostics
+			},
+			Err(PRdocLibError::ValidationErrors(diagnostics)) => {
+				log::info!("{} validation error(s)", diagnostics.len());
+				diagnostics
+			},
+			Err(PRdocLibError::SerdeYamlError(e)) => {
+				let mut diagnostic = Diagnostic::new(
+					Severity::Error,
+					CheckErrorKind::Syntax.code(),
+					e.to_string(),
+					file,
+					"",
+					&content,
+					None,
+				);
+				// Errors at the end of the file are reported on the line after the last one
+				if let Some(location) = e.location().filter(|l| l.line() > 0) {
+					let line = location.line().min(content.lines().count().max(1));
+					diagnostic = diagnostic.at_line(&content, line);
+				}
+				vec![diagnostic]
+			},
+			Err(e) => {
+				log::error!("Loading the schema failed: {e}");
+				return CheckReport::failed(
+					source,
+					Some(file.into()),
+					CheckErrorKind::Schema,
+					e.to_string(),
+				);
+			},
+		};
+
+		let diagnostics = self.linter.override_severity(diagnostics);
+		CheckReport { fix, ..CheckReport::new(source, file.into(), diagnostics) }
 	}
 
 	pub(crate) fn check_numbers(
 		&self,
-		numbers: Vec<PRNumber>,
-		dir: &PathBuf,
-	) -> error::Result<HashSet<CheckResult>> {
+		mut numbers: Vec<PRNumber>,
+		dirs: &[PathBuf],
+	) -> error::Result<Vec<CheckReport>> {
 		log::debug!("Checking PRs: {:?}", numbers);
 
-		let res = numbers
-			.iter()
-			.map(|&number| {
-				log::debug!("Checking PR #{}", number);
-
-				let file_maybe = DocFileName::find(number, None, dir);
-
-				match file_maybe {
-					Ok(file) => {
-						log::debug!("Attempting to load file: {}", file.display());
-						let yaml = self.schema.load(&file);
-
-						match yaml {
-							Ok(_value) => {
-								log::debug!("Loading was OK");
-								(number.into(), true)
-							},
-							Err(PRdocLibError::ValidationErrors(validation)) => {
-								log::info!("errors: {:#?}", validation.errors);
-								log::info!("missing: {:#?}", validation.missing);
-								(number.into(), false)
-							},
-							Err(e) => {
-								log::error!("Loading the schema failed:");
-								log::error!("{}", e.to_string());
-								(number.into(), false)
-							},
-						}
-					},
+		// A number given several times is checked once
+		let mut seen = HashSet::new();
+		numbers.retain(|number| seen.insert(*number));
+
+		// The files are searched first so that they can be checked in parallel
+		let mut targets: Vec<(PRNumber, Option<PathBuf>)> = vec![];
+		for number in numbers {
+			let files = DocFileName::find_all(number, dirs)?;
+			if files.is_empty() {
+				targets.push((number, None));
+			}
+			targets.extend(files.into_iter().map(|file| (number, Some(file))));
+		}
+
+		Ok(self.check_all(&targets, |(number, file)| match file {
+			Some(file) => {
+				log::debug!("Attempting to load file: {}", file.display());
+				self.validate((*number).into(), file)
+			},
+			None => {
+				let e = PRdocLibError::NumberNotFound(*number);
+				log::error!("{}", e.to_string());
+				let kind = CheckErrorKind::NotFound;
+				CheckReport::failed((*number).into(), None, kind, e.to_string())
+			},
+		}))
+	}
+
+	/// Report the PRDoc files of a same PR that cannot coexist, see
+	/// [check_parts](DocFileName::check_parts)
+	fn flag_duplicates(reports: Vec<CheckReport>) -> Vec<CheckReport> {
+		let mut files: BTreeMap<PRNumber, Vec<PathBuf>> = BTreeMap::new();
+		for report in &reports {
+			if let (Some(number), Some(file)) = (report_number(report), &report.file) {
+				files.entry(number).or_default().push(file.clone());
+			}
+		}
+
+		Self::flag_parts(reports, &files)
+	}
+
+	/// Flag the reports of the files that cannot coexist with the other `files` of their PR, see
+	/// [check_parts](DocFileName::check_parts)
+	fn flag_parts(
+		reports: Vec<CheckReport>,
+		files: &BTreeMap<PRNumber, Vec<PathBuf>>,
+	) -> Vec<CheckReport> {
+		reports
+			.into_iter()
+			.map(|report| {
+				let Some(number) = report_number(&report) else { return report };
+				let mut files = files.get(&number).cloned().unwrap_or_default();
+				// A file renamed by the fixes replaces its former name
+				if let Some(fix) = &report.fix {
+					files.retain(|file| file != &fix.file);
+				}
+				if let Some(file) = report.file.as_ref().filter(|file| !files.contains(file)) {
+					files.push(file.clone());
+				}
+
+				match DocFileName::check_parts(number, &files) {
+					Ok(()) => report,
 					Err(e) => {
-						log::error!("{}", e.to_string());
-						(number.into(), false)
+						let file = report.file.clone().unwrap_or_default();
+						let code = CheckErrorKind::Duplicate.code();
+						let diagnostic = Diagnostic::new(
+							Severity::Error,
+							code,
+							e.to_string(),
+							&file,
+							"",
+							"",
+							None,
+						);
+						report.with_diagnostic(diagnostic)
 					},
 				}
 			})
-			.collect();
-
-		Ok(res)
+			.collect()
 	}
 
 	/// Check a PRDoc based on its number in a given folder.
-	pub(crate) fn _check_number(
-		&self,
-		number: PRNumber,
-		dir: &PathBuf,
-	) -> error::Result<CheckResult> {
-		let file = DocFileName::find(number, None, dir)?;
-		Ok((file.clone().into(), self.check_file(&file).1))
-	}
-
 	/// Check a specific file given its full path.
 	/// All the other check_xxx functions are based on this one.
-	pub(crate) fn check_file(&self, file: &PathBuf) -> CheckResult {
+	pub(crate) fn check_file(&self, file: &PathBuf) -> CheckReport {
 		log::debug!("Checking file {}", file.display());
 
-		let value = self.schema.load(&file);
-		let filename_maybe = DocFileName::try_from(file);
-		if let Ok(_value) = value {
-			if let Ok(filename) = filename_maybe {
-				(filename.into(), true)
-			} else {
-				(file.into(), false)
-			}
-		} else if let Ok(f) = filename_maybe {
-			(f.into(), false)
-		} else {
-			(file.into(), false)
+		let source = match DocFileName::try_from(file) {
+			Ok(filename) => filename.into(),
+			Err(_) => file.into(),
+		};
+		let report = self.validate(source, file);
+
+		// The file may have been renamed by the fixes
+		let checked = report.file.clone().unwrap_or_else(|| file.clone());
+		match DocFileName::try_from(&checked) {
+			Ok(_) => report,
+			Err(e) => {
+				let diagnostic = Diagnostic::new(
+					Severity::Error,
+					CheckErrorKind::InvalidFilename.code(),
+					e.to_string(),
+					&checked,
+					"",
+					"",
+					None,
+				)
+				.with_hint(DocFileName::explain(&checked).map(|explanation| {
+					format!("{explanation}, expected `pr_<number>[_<title>].prdoc`")
+				}));
+				report.with_diagnostic(diagnostic)
+			},
 		}
 	}
 
-	/// Check all files in a given folder. The dot files (ie filenames starting with a dot) are
+	/// Check all files in the given folders. The dot files (ie filenames starting with a dot) are
 	/// ignored This functions allows checking all files or only the valid ones thanks to the
-	/// `valid_only` argument.
-	pub(crate) fn check_files_in_folder(
-		self,
-		dir: &PathBuf,
+	/// `valid_only` argument. The reports follow the order of the folders then of the filenames.
+	pub(crate) fn check_files_in_folders(
+		&self,
+		dirs: &[PathBuf],
 		valid_only: bool,
-	) -> error::Result<HashSet<CheckResult>> {
-		log::debug!("Checking all files in folder {}", dir.display());
+	) -> error::Result<Vec<CheckReport>> {
+		let mut files = vec![];
+		for dir in dirs {
+			log::debug!("Checking all files in folder {}", dir.display());
+
+			let schema = self.schema.clone();
+			let mut dir_files: Vec<PathBuf> = DocFile::find(schema, dir, valid_only)?
+				.filter(|f| !f.file_name().unwrap_or_default().to_string_lossy().starts_with('.'))
+				.collect();
+			dir_files.sort();
+			files.extend(dir_files);
+		}
 
-		let schema = self.schema.clone();
-		let files = DocFile::find(schema, dir, valid_only)?
-			.filter(|f| !f.file_name().unwrap_or_default().to_string_lossy().starts_with('.'));
-		let hs: HashSet<CheckResult> = files.map(|f| self.check_file(&f)).collect();
-		Ok(hs)
+		Ok(self.check_all(&files, |file| self.check_file(file)))
 	}
 
 	/// Check a list of PRDoc files based on:
 	///  - a `file` containing the list of PR numbers
-	///  - a base `dir` where to look for those PRDoc files
+	///  - the `dirs` where to look for those PRDoc files
 	pub(crate) fn check_list(
 		&self,
 		file: &PathBuf,
-		dir: &PathBuf,
-	) -> error::Result<HashSet<CheckResult>> {
+		dirs: &[PathBuf],
+	) -> error::Result<Vec<CheckReport>> {
 		let extract_numbers = get_numbers_from_file(file)?;
 
 		let numbers: Vec<PRNumber> =
 			extract_numbers.iter().filter_map(|(_, _, n)| n.to_owned()).collect();
 
-		self.check_numbers(numbers, dir)
+		self.check_numbers(numbers, dirs)
 	}
 
 	/// Return true if all checks were OK, false otherwise.
-	pub fn global_result(hs: HashSet<CheckResult>) -> bool {
-		for item in hs.iter() {
-			if !item.1 {
-				return false;
-			}
-		}
-
-		true
+	pub fn global_result(hs: HashSet<CheckReport>) -> bool {
+		hs.iter().all(|report| report.is_valid())
 	}
 
-	/// Run the check: considering an input directory and either a file, some numbers, of a list
-	/// file, run thru the list and check the validity of the PRDoc files.
-	/// We return a Vec instead of a HashSet because a check based on a file may not always lead
-	/// to a PR number, making the HashSet made of a bunch of (None, bool).
+	/// Run the check: considering the input directories and either a file, some numbers, of a
+	/// list file, run thru the list and check the validity of the PRDoc files.
+	/// The PRDoc files of a same PR are searched in all the directories and reported as
+	/// duplicates when they cannot coexist.
 	pub fn run(
 		config: &PRDocConfig,
-		schema: Option<PathBuf>,
-		dir: &PathBuf,
+		schema: Option<SchemaSource>,
+		dirs: &[PathBuf],
 		file: Option<PathBuf>,
 		numbers: Option<Vec<PRNumber>>,
 		list: Option<PathBuf>,
-	) -> crate::error::Result<HashSet<CheckResult>> {
-		log::info!("Checking directory {}", dir.display());
-		log::debug!("From dir: {}", dir.canonicalize().unwrap().display());
+		fix: Option<FixMode>,
+	) -> crate::error::Result<Vec<CheckReport>> {
+		log::info!("Checking directories {:?}", dirs);
+		let check_cmd = Self::from_config(config, schema, fix)?;
 
-		let repo_root = get_project_root()?;
-		log::debug!("From repo root: {}", repo_root.canonicalize().unwrap().display());
-
-		let schema_path = if let Some(schema_path) = schema {
-			schema_path
-		} else if config.schema_path().is_absolute() {
-			config.schema_path()
-		} else {
-			repo_root.join(config.schema_path())
+		let reports = match (file, numbers, list) {
+			(Some(file), None, None) => vec![check_cmd.check_file(&resolve_file(file, dirs))],
+			(None, Some(numbers), None) => check_cmd.check_numbers(numbers, dirs)?,
+			(None, None, Some(list)) => check_cmd.check_list(&list, dirs)?,
+			(None, None, None) => check_cmd.check_files_in_folders(dirs, false)?,
+
+			_ =>
+				return Err(PRdocLibError::InvalidArguments(
+					"only one of a file, numbers or a list can be checked".into(),
+				)),
 		};
 
-		log::info!("Using schema: {}", schema_path.canonicalize().unwrap().display());
-		let schema = Schema::new(schema_path);
+		Ok(Self::flag_duplicates(reports))
+	}
 
-		let check_cmd = CheckCmd::new(schema);
+	/// Run the check on the PRDoc files that were added or modified in the `changes`. The
+	/// changed files must also coexist with the unchanged files of their PR found in the `dirs`.
+	/// The deleted files are left to the caller.
+	pub fn run_changed(
+		config: &PRDocConfig,
+		schema: Option<SchemaSource>,
+		changes: &ChangedPRDocs,
+		dirs: &[PathBuf],
+		fix: Option<FixMode>,
+	) -> crate::error::Result<Vec<CheckReport>> {
+		log::info!("Checking the changed files {:?}", changes.changed);
+		let check_cmd = Self::from_config(config, schema, fix)?;
 
-		match (file, numbers, list) {
-			(Some(file), None, None) => {
-				let file =
-					if file.is_relative() { Path::new(&dir).join(&file) } else { file.clone() };
+		let reports = check_cmd.check_all(&changes.changed, |file| check_cmd.check_file(file));
+		Ok(Self::flag_parts(reports, &Self::siblings(&changes.changed, dirs)?))
+	}
 
-				let mut hs = HashSet::new();
-				let _ = hs.insert(check_cmd.check_file(&file));
-				Ok(hs)
-			},
+	/// Return the PRDoc files found in the `dirs` for the PR of each of the `files`
+	fn siblings(
+		files: &[PathBuf],
+		dirs: &[PathBuf],
+	) -> crate::error::Result<BTreeMap<PRNumber, Vec<PathBuf>>> {
+		let mut siblings: BTreeMap<PRNumber, Vec<PathBuf>> = BTreeMap::new();
+		for file in files {
+			let Ok(filename) = DocFileName::try_from(file) else { continue };
+			if let Entry::Vacant(entry) = siblings.entry(filename.number) {
+				entry.insert(DocFileName::find_all(filename.number, dirs)?);
+			}
+		}
+		Ok(siblings)
+	}
+
+	/// Create the check command using the schema and the lints of the `config`
+	fn from_config(
+		config: &PRDocConfig,
+		schema: Option<SchemaSource>,
+		fix: Option<FixMode>,
+	) -> crate::error::Result<Self> {
+		let repo_root = get_project_root()?;
+		log::debug!("From repo root: {}", repo_root.display());
+
+		let schema = config.load_schema(schema, &repo_root)?;
+		Ok(CheckCmd::new(schema)
+			.with_linter(config.linter(&repo_root)?)
+			.with_fix(fix)
+			.with_jobs(config.jobs()))
+	}
+}
 
-			(None, Some(numbers), None) => check_cmd.check_numbers(numbers, dir),
-			(None, None, Some(list)) => check_cmd.check_list(&list, dir),
-			(None, None, None) => check_cmd.check_files_in_folder(dir, false),
+/// Return the PR number of the file of the `report`, if any
+fn report_number(report: &CheckReport) -> Option<PRNumber> {
+	report
+		.file
+		.as_ref()
+		.and_then(|f| DocFileName::try_from(f).ok())
+		.map(|f| f.number)
+}
+
+#[cfg(test)]
+mod test_check {
+	use super::*;
+	use crate::utils::TestDir;
+
+	fn check(name: &str, content: &str) -> CheckReport {
+		let dir = TestDir::new("check");
+		let file = dir.write(name, content);
+		CheckCmd::new(Schema::builtin("v1").unwrap()).check_file(&file)
+	}
+
+	#[test]
+	fn test_check_report() {
+		let valid = fs::read_to_string("tests/data/all/pr_1237.prdoc").unwrap();
+
+		let report = check("pr_1.prdoc", &valid);
+		assert!(report.is_valid());
+		assert_eq!(None, report.error);
+		assert_eq!(Some(1), report.number());
 
-			_ => unreachable!(),
+		// The fixture comes with a lint warning
+		assert_eq!(CheckStatus::Warning, report.status);
+		let report = report.deny_warnings();
+		assert!(!report.is_valid());
+		assert_eq!(Some(CheckErrorKind::Warnings), report.error);
+		assert_eq!(Severity::Warning, report.diagnostics[0].severity);
+
+		let report = check("pr_2.prdoc", "title: [");
+		assert_eq!(CheckStatus::Invalid, report.status);
+		assert_eq!(Some(CheckErrorKind::Syntax), report.error);
+		assert_eq!(1, report.diagnostics[0].range.unwrap().start.line);
+
+		let report = check("pr_3.prdoc", "title: Foo\n");
+		assert_eq!(Some(CheckErrorKind::Schema), report.error);
+
+		let report = check(
+			"pr_4.prdoc",
+			&format!("# Schema: Polkadot SDK PRDoc Schema (prdoc) v9.0.0\n{valid}"),
+		);
+		assert_eq!(Some(CheckErrorKind::MissingSchema), report.error);
+
+		let report = check("not_a_prdoc.yml", &valid);
+		assert_eq!(Some(CheckErrorKind::InvalidFilename), report.error);
+		let diagnostic = report.diagnostics.iter().find(|d| d.code == "prdoc/invalid-filename");
+		assert_eq!(
+			Some("the extension must be `.prdoc`, expected `pr_<number>[_<title>].prdoc`"),
+			diagnostic.and_then(|d| d.hint.as_deref())
+		);
+
+		let cmd = CheckCmd::new(Schema::builtin("v1").unwrap());
+		let report = cmd.check_numbers(vec![9999], &[PathBuf::from("tests/data/all")]).unwrap();
+		let report = report.into_iter().next().unwrap();
+		assert_eq!(Some(CheckErrorKind::NotFound), report.error);
+		assert_eq!(None, report.file);
+		assert_eq!(1, report.messages.len());
+	}
+
+	#[test]
+	fn test_fix() {
+		let dir = TestDir::new("check-fix");
+		let valid = fs::read_to_string("tests/data/all/pr_1237.prdoc").unwrap();
+		let broken = valid
+			.replace("semver: patch", "semver: Patch")
+			.replace("host_functions: []", "");
+		let file = dir.write("pr_5 - foo.prdoc", &broken);
+
+		let cmd = |mode| CheckCmd::new(Schema::builtin("v1").unwrap()).with_fix(Some(mode));
+
+		let report = cmd(FixMode::DryRun).check_file(&file);
+		assert!(report.is_valid());
+		let fix = report.fix.unwrap();
+		assert_eq!(3, fix.changes.len());
+		assert!(!fix.written);
+		assert!(fix.diff().contains("+host_functions: []"));
+		assert_eq!(broken, fs::read_to_string(&file).unwrap());
+
+		let report = cmd(FixMode::Apply).check_file(&file);
+		assert!(report.is_valid());
+		assert!(report.fix.unwrap().written);
+		assert!(!file.exists());
+		let fixed = fs::read_to_string(dir.join("pr_5_foo.prdoc")).unwrap();
+		assert!(fixed.contains("semver: patch"));
+
+		let report =
+			CheckCmd::new(Schema::builtin("v1").unwrap()).check_file(&dir.join("pr_5_foo.prdoc"));
+		assert!(report.is_valid());
+		assert_eq!(None, report.fix);
+	}
+
+	#[test]
+	fn test_duplicates() {
+		let dir = TestDir::new("check-duplicates");
+		let valid = fs::read_to_string("tests/data/all/pr_1237.prdoc").unwrap();
+		for name in ["pr_7.prdoc", "pr_7_.prdoc", "pr_8_part1.prdoc", "pr_8_part2_foo.prdoc"] {
+			dir.write(name, &valid);
+		}
+
+		let cmd = CheckCmd::new(Schema::builtin("v1").unwrap());
+		let reports = cmd.check_numbers(vec![7, 8], &[dir.to_path_buf()]).unwrap();
+		let reports = CheckCmd::flag_duplicates(reports);
+
+		let errors: Vec<_> = reports.iter().map(|r| r.error).collect();
+		assert_eq!(
+			vec![Some(CheckErrorKind::Duplicate), Some(CheckErrorKind::Duplicate), None, None],
+			errors
+		);
+		assert!(reports[0].diagnostics.iter().any(|d| d.message.contains("pr_7_.prdoc")));
+
+		// A number repeated on the command line or in a list is checked once
+		let list = dir.write("list.txt", "8\n8\n");
+		for reports in [
+			cmd.check_numbers(vec![8, 8], &[dir.to_path_buf()]).unwrap(),
+			cmd.check_list(&list, &[dir.to_path_buf()]).unwrap(),
+		] {
+			let reports = CheckCmd::flag_duplicates(reports);
+			assert_eq!(vec![None, None], reports.iter().map(|r| r.error).collect::<Vec<_>>());
 		}
 	}
+
+	#[test]
+	fn test_changed() {
+		let repo = TestDir::new("check-changed");
+		let dir = repo.join("prdoc");
+
+		repo.git(&["init", "-q"]);
+		for name in ["prdoc/pr_1.prdoc", "prdoc/pr_2.prdoc", "prdoc/pr_3.prdoc"] {
+			repo.write(name, "title: Foo\n");
+		}
+		repo.git(&["add", "."]);
+		repo.git(&["commit", "-q", "-m", "base"]);
+		repo.git(&["tag", "base"]);
+
+		repo.write("prdoc/pr_1.prdoc", "title: Bar\n");
+		repo.write("prdoc/pr_3_foo.prdoc", "title: Foo\n");
+		repo.write("prdoc/pr_4.prdoc", "title: Foo\n");
+		repo.write("pr_5.prdoc", "title: Foo\n");
+		repo.write("prdoc/notes.txt", "");
+		fs::remove_file(dir.join("pr_2.prdoc")).unwrap();
+		repo.git(&["add", "-A"]);
+
+		let changes =
+			ChangedPRDocs::find(&Git::new(&repo), "base", true, std::slice::from_ref(&dir))
+				.unwrap();
+		assert_eq!(
+			vec![dir.join("pr_1.prdoc"), dir.join("pr_3_foo.prdoc"), dir.join("pr_4.prdoc")],
+			changes.changed
+		);
+		assert_eq!(vec![dir.join("pr_2.prdoc")], changes.deleted);
+
+		// The new file of PR #3 clashes with the unchanged one
+		let siblings = CheckCmd::siblings(&changes.changed, std::slice::from_ref(&dir)).unwrap();
+		assert_eq!(vec![dir.join("pr_3.prdoc"), dir.join("pr_3_foo.prdoc")], siblings[&3]);
+		let cmd = CheckCmd::new(Schema::builtin("v1").unwrap());
+		let reports = cmd.check_all(&changes.changed, |file| cmd.check_file(file));
+		let duplicates: Vec<bool> = CheckCmd::flag_parts(reports, &siblings)
+			.iter()
+			.map(|r| r.diagnostics.iter().any(|d| d.code == CheckErrorKind::Duplicate.code()))
+			.collect();
+		assert_eq!(vec![false, true, false], duplicates);
+
+		// A changed file renamed by the fixes is not the duplicate of its former name
+		let valid = fs::read_to_string("tests/data/all/pr_1237.prdoc").unwrap();
+		repo.write("prdoc/pr_6 - foo.prdoc", &valid.replace("host_functions: []", ""));
+		repo.git(&["add", "-A"]);
+		let changes =
+			ChangedPRDocs { changed: vec![dir.join("pr_6 - foo.prdoc")], ..Default::default() };
+		let schema = Some(SchemaSource::Builtin("v1".into()));
+		let config = PRDocConfig::default();
+		let dirs = std::slice::from_ref(&dir);
+		for mode in [FixMode::DryRun, FixMode::Apply] {
+			let reports =
+				CheckCmd::run_changed(&config, schema.clone(), &changes, dirs, Some(mode)).unwrap();
+			assert_eq!(Some(dir.join("pr_6_foo.prdoc")), reports[0].file);
+			assert!(reports[0].is_valid(), "{:?}", reports[0].diagnostics);
+		}
+		assert!(dir.join("pr_6_foo.prdoc").exists());
+
+		// Without `staged`, only the commits are considered
+		let changes = ChangedPRDocs::find(&Git::new(&repo), "base", false, &[dir]).unwrap();
+		assert_eq!(ChangedPRDocs::default(), changes);
+	}
 }
@@ -22,8 +22,8 @@ impl GenerateCmd {
 			match get_project_root() {
 				Ok(dir) => dir.join(PRDOC_DEFAULT_DIR),
 				Err(e) => {
-					eprint!("Project root not found, falling back to the current folder: {e:?}");
-					PathBuf::from("../..")
+					log::warn!("{e}, falling back to the current folder");
+					PathBuf::from(".")
 				},
 			}
 		}
@@ -49,9 +49,7 @@ impl GenerateCmd {
 			let filename: PathBuf = DocFileName::new(number, title).into();
 			let output_dir = Self::get_output_dir(output_dir);
 			log::debug!("Storing prdoc in {output_dir:?}");
-			std::fs::create_dir_all(&output_dir).unwrap_or_else(|why| {
-				println!("! {:?}", why.kind());
-			});
+			std::fs::create_dir_all(&output_dir)?;
 
 			let output_file = Path::new(&output_dir).join(filename);
 			log::debug!("output_file = {:?}", &output_file);
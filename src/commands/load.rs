@@ -8,67 +8,231 @@ use crate::{
 	doc_filename::DocFileName,
 	docfile::DocFile,
 	docfile_wrapper::DocFileWrapper,
-	error::{self, Result},
-	schema::Schema,
-	utils::{get_numbers_from_file, get_project_root},
+	error::{PRdocLibError, Result},
+	filter::Filter,
+	schema::{Schema, SchemaSource},
+	utils::{get_numbers_from_file, get_project_root, par_for_each, resolve_file},
 };
+use clap::ValueEnum;
 use std::{
-	collections::HashSet,
-	path::{Path, PathBuf},
+	collections::{BTreeMap, HashSet},
+	fs,
+	path::PathBuf,
 };
 
 /// Wrapper for the load command
 pub struct LoadCmd {
 	pub(crate) schema: Schema,
+	pub(crate) jobs: usize,
 }
 
-/// Type alias for the load command result
-pub type LoadResult = (bool, HashSet<DocFileWrapper>);
+/// Type alias for the load command result. The PRDoc are sorted by PR number, then by path, and
+/// can be sorted differently with [LoadCmd::sort].
+pub type LoadResult = (bool, Vec<DocFileWrapper>);
+
+/// Order of the loaded PRDoc
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+	/// PR number
+	#[default]
+	Number,
+
+	/// Title of the PRDoc, ignoring the case. The title in the filename is used if the PRDoc could
+	/// not be loaded.
+	Title,
+
+	/// Path of the file
+	Path,
+
+	/// Last modification of the file, the oldest first. The latest part is used for the PRDoc
+	/// split in several parts.
+	Mtime,
+}
 
 impl LoadCmd {
 	/// Create a new instance of the load command
 	pub fn new(schema: Schema) -> Self {
-		Self { schema }
+		Self { schema, jobs: 1 }
+	}
+
+	/// Load up to `jobs` files in parallel. The result does not depend on the number of jobs.
+	pub fn with_jobs(mut self, jobs: usize) -> Self {
+		self.jobs = jobs;
+		self
+	}
+
+	/// Create the load command with the schema given on the command line if any, the one of the
+	/// `config` otherwise
+	pub fn from_config(config: &PRDocConfig, schema: Option<SchemaSource>) -> Result<Self> {
+		let repo_root = get_project_root()?;
+		log::debug!("From repo root: {}", repo_root.display());
+
+		let schema = config.load_schema(schema, &repo_root)?;
+		Ok(LoadCmd::new(schema).with_jobs(config.jobs()))
 	}
 
-	/// Load PRDoc from one or more numbers
+	/// Load PRDoc from one or more numbers. The files are searched in all the `dirs`.
 	pub(crate) fn load_numbers(
 		&self,
-		numbers: Vec<PRNumber>,
-		dir: &PathBuf,
-	) -> error::Result<LoadResult> {
+		mut numbers: Vec<PRNumber>,
+		dirs: &[PathBuf],
+		on_prdoc: impl FnMut(DocFileWrapper) -> Result<()>,
+	) -> Result<bool> {
 		let mut global_result = true;
+		let mut files = vec![];
 
-		let res = numbers
-			.iter()
-			.filter_map(|&number| {
-				log::debug!("Loading PR #{}", number);
-				let file_maybe = DocFileName::find(number, None, dir);
-
-				match file_maybe {
-					Ok(file) => {
-						let filename = DocFileName::try_from(&file)
-							.expect("If we found a file, it should be valid");
-
-						let yaml = self.schema.load(&file);
-
-						if let Ok(value) = yaml {
-							Some(DocFileWrapper::new(file, filename, Some(value)))
-						} else {
-							global_result &= false;
-							None
-						}
-					},
-					Err(e) => {
+		// A number given several times is loaded once
+		let mut seen = HashSet::new();
+		numbers.retain(|number| seen.insert(*number));
+		for number in numbers {
+			log::debug!("Loading PR #{}", number);
+			let found = DocFileName::find_all(number, dirs)?;
+			if found.is_empty() {
+				global_result &= false;
+				log::warn!("{}", PRdocLibError::NumberNotFound(number));
+			}
+			files.extend(found);
+		}
+
+		let loaded = self.load_files(files, on_prdoc)?;
+		Ok(global_result && loaded)
+	}
+
+	/// Load the `files` and pass each PRDoc to `on_prdoc` as soon as it is loaded. The files are
+	/// loaded by PR number, then by path, so that only the parts of a same PRDoc wait for each
+	/// other to be merged. The files with an invalid name are skipped while the ones that could
+	/// not be loaded make the result fail.
+	fn load_files(
+		&self,
+		files: Vec<PathBuf>,
+		mut on_prdoc: impl FnMut(DocFileWrapper) -> Result<()>,
+	) -> Result<bool> {
+		let mut named = vec![];
+		for file in files {
+			match DocFileName::try_from(&file) {
+				Ok(filename) => named.push((filename.number, file)),
+				Err(_) => log::warn!("Invalid file {:?}", file.display()),
+			}
+		}
+		named.sort();
+
+		let mut global_result = true;
+		let mut parts: Vec<DocFileWrapper> = vec![];
+		par_for_each(
+			&named,
+			self.jobs,
+			|(_, file)| {
+				let filename = DocFileName::try_from(file).map_err(|_| file.clone())?;
+				let value = self.schema.load(file).map_err(|_| file.clone())?;
+				Ok(DocFileWrapper::new(file.clone(), filename, Some(value)))
+			},
+			|loaded: std::result::Result<DocFileWrapper, PathBuf>| -> Result<()> {
+				let wrapper = match loaded {
+					Ok(wrapper) => wrapper,
+					Err(file) => {
+						log::warn!("ERR {}", file.display());
 						global_result &= false;
-						log::warn!("{e:?}");
-						None
+						return Ok(());
 					},
+				};
+				log::debug!("OK  {}", wrapper.file.display());
+
+				if parts
+					.last()
+					.is_some_and(|last| last.doc_filename.number != wrapper.doc_filename.number)
+				{
+					global_result &=
+						Self::merge_and_send(std::mem::take(&mut parts), &mut on_prdoc)?;
 				}
-			})
-			.collect();
+				parts.push(wrapper);
+				Ok(())
+			},
+		)?;
+
+		global_result &= Self::merge_and_send(parts, &mut on_prdoc)?;
+		Ok(global_result)
+	}
 
-		Ok((global_result, res))
+	/// Merge the files of a same PR, see [merge_parts](Self::merge_parts), and pass the result to
+	/// `on_prdoc`
+	fn merge_and_send(
+		wrappers: Vec<DocFileWrapper>,
+		on_prdoc: &mut impl FnMut(DocFileWrapper) -> Result<()>,
+	) -> Result<bool> {
+		let (merged, wrappers) = Self::merge_parts(wrappers);
+		wrappers.into_iter().try_for_each(on_prdoc)?;
+		Ok(merged)
+	}
+
+	/// Merge the PRDoc split in several parts. PRDoc files of a same PR that cannot coexist are
+	/// kept apart and make the result fail, see [check_parts](DocFileName::check_parts).
+	pub(crate) fn merge_parts(wrappers: Vec<DocFileWrapper>) -> LoadResult {
+		let mut global_result = true;
+		let mut by_number: BTreeMap<PRNumber, Vec<DocFileWrapper>> = BTreeMap::new();
+		for wrapper in wrappers {
+			by_number.entry(wrapper.doc_filename.number).or_default().push(wrapper);
+		}
+
+		let mut res = vec![];
+		for (number, mut wrappers) in by_number {
+			wrappers.sort_by(|a, b| a.file.cmp(&b.file));
+			let files: Vec<PathBuf> = wrappers.iter().map(|w| w.file.clone()).collect();
+			match DocFileName::check_parts(number, &files) {
+				Ok(()) if wrappers.len() > 1 => {
+					res.push(DocFileWrapper::merge(wrappers));
+				},
+				Ok(()) => res.extend(wrappers),
+				Err(e) => {
+					log::error!("{e}");
+					global_result &= false;
+					res.extend(wrappers);
+				},
+			}
+		}
+
+		(global_result, res)
+	}
+
+	/// Sort the `wrappers` by the given key. The PRDoc with the same key are sorted by PR number
+	/// then by path so that the order never changes from one run to the other. With `reverse`, the
+	/// whole order is reversed.
+	pub fn sort(wrappers: &mut [DocFileWrapper], by: SortBy, reverse: bool) {
+		let default = |a: &DocFileWrapper, b: &DocFileWrapper| {
+			a.doc_filename
+				.number
+				.cmp(&b.doc_filename.number)
+				.then_with(|| a.file.cmp(&b.file))
+		};
+
+		match by {
+			SortBy::Number => wrappers.sort_by(default),
+			SortBy::Path =>
+				wrappers.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| default(a, b))),
+			SortBy::Title => wrappers.sort_by_cached_key(|wrapper| {
+				let title = match (&wrapper.prdoc, &wrapper.doc_filename.title) {
+					(Some(prdoc), _) => prdoc.title.clone(),
+					(None, Some(title)) => title.to_string(),
+					(None, None) => String::new(),
+				};
+				(title.to_lowercase(), wrapper.doc_filename.number, wrapper.file.clone())
+			}),
+			SortBy::Mtime => wrappers.sort_by_cached_key(|wrapper| {
+				let files = if wrapper.parts.is_empty() {
+					std::slice::from_ref(&wrapper.file)
+				} else {
+					wrapper.parts.as_slice()
+				};
+				let mtime = files
+					.iter()
+					.filter_map(|file| fs::metadata(file).and_then(|m| m.modified()).ok())
+					.max();
+				(mtime, wrapper.doc_filename.number, wrapper.file.clone())
+			}),
+		}
+
+		if reverse {
+			wrappers.reverse();
+		}
 	}
 
 	/// Load one file and returns a wrapper
@@ -79,104 +243,214 @@ impl LoadCmd {
 		Ok(wrapper)
 	}
 
-	pub(crate) fn load_list(&self, file: &PathBuf, dir: &PathBuf) -> Result<LoadResult> {
+	pub(crate) fn load_list(
+		&self,
+		file: &PathBuf,
+		dirs: &[PathBuf],
+		on_prdoc: impl FnMut(DocFileWrapper) -> Result<()>,
+	) -> Result<bool> {
 		let extract_numbers = get_numbers_from_file(file)?;
 		let numbers: Vec<PRNumber> =
 			extract_numbers.iter().filter_map(|(_, _, n)| n.to_owned()).collect();
 
-		let mut global_result = extract_numbers.iter().map(|(_, status, _)| status).all(|&x| x);
+		let global_result = extract_numbers.iter().map(|(_, status, _)| status).all(|&x| x);
 
-		let (r, wrapper) = self.load_numbers(numbers, dir).unwrap();
-		global_result &= r;
-		Ok((global_result, wrapper))
+		let loaded = self.load_numbers(numbers, dirs, on_prdoc)?;
+		Ok(global_result && loaded)
 	}
 
-	pub(crate) fn load_from_folder(&self, dir: &PathBuf) -> Result<LoadResult> {
-		let res = DocFile::find(self.schema.clone(), dir, false);
-		let mut global_result = res.is_ok();
+	/// Load the PRDoc like [run](Self::run) but pass each of them to `on_prdoc` as soon as it is
+	/// loaded instead of returning them all at the end. The PRDoc come sorted by PR number, then by
+	/// path. Returns false if some PRDoc could not be loaded.
+	pub fn for_each(
+		&self,
+		dirs: &[PathBuf],
+		file: Option<PathBuf>,
+		numbers: Option<Vec<PRNumber>>,
+		list: Option<PathBuf>,
+		filter: &Filter,
+		mut on_prdoc: impl FnMut(DocFileWrapper) -> Result<()>,
+	) -> Result<bool> {
+		log::debug!("Loading from directories {:?}", dirs);
+		log::debug!("Filtering with {:?}", filter);
 
-		let wrapper = res
-			.unwrap()
-			.filter_map(|file| {
-				let filename_maybe = DocFileName::try_from(&file);
+		let mut on_prdoc = |wrapper: DocFileWrapper| {
+			if filter.is_empty() || wrapper.prdoc.as_ref().is_some_and(|doc| filter.matches(doc)) {
+				on_prdoc(wrapper)
+			} else {
+				Ok(())
+			}
+		};
 
-				if let Ok(filename) = filename_maybe {
-					let yaml = self.schema.load(&file);
-					if let Ok(value) = yaml {
-						let wrapper = DocFileWrapper::new(file.clone(), filename, Some(value));
+		match (file, numbers, list) {
+			(Some(f), None, None) => {
+				on_prdoc(self.load_file(&resolve_file(f, dirs))?)?;
+				Ok(true)
+			},
 
-						global_result &= true;
-						log::debug!("OK  {}", file.display());
-						Some(wrapper)
-					} else {
-						global_result &= false;
-						log::warn!("ERR {}", file.display());
-						None
-					}
-				} else {
-					log::warn!("Invalid file {:?}", file.display());
-					None
+			(None, Some(numbers), None) => {
+				log::debug!("Loading numbers {:?}", numbers);
+				self.load_numbers(numbers, dirs, on_prdoc)
+			},
+
+			(None, None, Some(list)) => {
+				log::debug!("Loading list from {:?}", list);
+				self.load_list(&list, dirs, on_prdoc)
+			},
+
+			(None, None, None) => {
+				let mut files = vec![];
+				for dir in dirs {
+					log::debug!("Loading all files in folder {}", dir.display());
+					files.extend(DocFile::find(self.schema.clone(), dir, false)?);
 				}
-			})
-			.collect();
+				self.load_files(files, on_prdoc)
+			},
 
-		Ok((global_result, wrapper))
+			_ => Err(PRdocLibError::InvalidArguments(
+				"only one of a file, numbers or a list can be loaded".into(),
+			)),
+		}
 	}
 
-	/// Run of the load command
+	/// Run of the load command. The PRDoc files are searched in all the `dirs` and the PRDoc
+	/// split in several parts are merged before being selected by the `filter`. The files that
+	/// could not be loaded are only kept when the filter is empty.
 	pub fn run(
 		config: &PRDocConfig,
-		schema: Option<PathBuf>,
-		dir: &PathBuf,
+		schema: Option<SchemaSource>,
+		dirs: &[PathBuf],
 		file: Option<PathBuf>,
 		numbers: Option<Vec<PRNumber>>,
 		list: Option<PathBuf>,
+		filter: &Filter,
 	) -> Result<LoadResult> {
-		log::debug!("Loading from directory {}", dir.display());
+		let load_cmd = Self::from_config(config, schema)?;
 
-		let repo_root = get_project_root()?;
-		log::debug!("From repo root: {}", repo_root.canonicalize().unwrap().display());
-
-		let schema_path = if let Some(schema_path) = schema {
-			schema_path
-		} else if config.schema_path().is_absolute() {
-			config.schema_path()
-		} else {
-			repo_root.join(config.schema_path())
+		let mut wrappers = vec![];
+		let global_result = load_cmd.for_each(dirs, file, numbers, list, filter, |wrapper| {
+			wrappers.push(wrapper);
+			Ok(())
+		})?;
+		Ok((global_result, wrappers))
+	}
+}
+
+#[cfg(test)]
+mod test_load {
+	use super::*;
+	use crate::utils::TestDir;
+	use std::fs;
+
+	#[test]
+	fn test_load_parts() {
+		let dir = TestDir::new("load-parts");
+		let valid = fs::read_to_string("tests/data/all/pr_1237.prdoc").unwrap();
+		dir.write("pr_8_part1.prdoc", &valid);
+		dir.write("pr_8_part2.prdoc", &valid.replace("Foobar", "Other"));
+
+		let load_cmd = LoadCmd::new(Schema::builtin("v1").unwrap());
+		let mut wrappers = vec![];
+		let result = load_cmd
+			.load_numbers(vec![8], &[dir.to_path_buf()], |wrapper| {
+				wrappers.push(wrapper);
+				Ok(())
+			})
+			.unwrap();
+		assert!(result);
+
+		// A number repeated on the command line or in a list is loaded once
+		let list = dir.write("list.txt", "8\n8\n");
+		let dirs = [dir.to_path_buf()];
+		let mut count = 0;
+		let mut on_prdoc = |_| {
+			count += 1;
+			Ok(())
 		};
-		log::info!("Using schema: {}", schema_path.canonicalize().unwrap().display());
+		assert!(load_cmd.load_numbers(vec![8, 8], &dirs, &mut on_prdoc).unwrap());
+		assert!(load_cmd.load_list(&list, &dirs, &mut on_prdoc).unwrap());
+		assert_eq!(2, count);
+		assert_eq!(1, wrappers.len());
 
-		let schema = Schema::new(schema_path);
-		let load_cmd = LoadCmd::new(schema);
+		let wrapper = wrappers.into_iter().next().unwrap();
+		assert_eq!(2, wrapper.parts.len());
+		let prdoc = wrapper.prdoc.unwrap();
+		assert_eq!("Foobar", prdoc.title);
+		assert_eq!(4, prdoc.doc.len());
+		assert_eq!(6, prdoc.crates.len());
+	}
 
-		Ok(match (file, numbers, list) {
-			(Some(f), None, None) => {
-				let file_abs = if f.is_relative() { Path::new(&dir).join(&f) } else { f.clone() };
-				let mut wrapper = HashSet::new();
-				wrapper.insert(load_cmd.load_file(&file_abs)?);
+	#[test]
+	fn test_for_each_streams() {
+		let dir = TestDir::new("load-streams");
+		let valid = fs::read_to_string("tests/data/all/pr_1237.prdoc").unwrap();
+		for file in ["pr_1.prdoc", "pr_2_part1.prdoc", "pr_2_part2.prdoc", "pr_3.prdoc"] {
+			dir.write(file, &valid);
+		}
 
-				(true, wrapper)
-			},
+		// PR #1 is received before the file of PR #3 is even loaded
+		let load_cmd = LoadCmd::new(Schema::builtin("v1").unwrap());
+		let mut received = vec![];
+		let result = load_cmd
+			.for_each(&[dir.to_path_buf()], None, None, None, &Filter::default(), |wrapper| {
+				if wrapper.doc_filename.number == 1 {
+					dir.write("pr_3.prdoc", &valid.replace("Foobar", "Late"));
+				}
+				received.push((
+					wrapper.doc_filename.number,
+					wrapper.parts.len(),
+					wrapper.prdoc.unwrap().title,
+				));
+				Ok(())
+			})
+			.unwrap();
+		assert!(result);
+		assert_eq!(
+			vec![(1, 0, "Foobar".into()), (2, 2, "Foobar".into()), (3, 0, "Late".into())],
+			received
+		);
+	}
 
-			(None, Some(numbers), None) => {
-				log::debug!("Loading numbers {:?}", numbers);
-				let (global_result, wrapper) = load_cmd.load_numbers(numbers, dir)?;
-				(global_result, wrapper)
-			},
+	#[test]
+	fn test_sort() {
+		let wrapper = |number: PRNumber, file: &str, title: &str| {
+			let value: serde_yaml::Value =
+				serde_yaml::from_str(&format!("title: {title}")).unwrap();
+			let prdoc = crate::model::PrDoc::try_from(&value).unwrap();
+			DocFileWrapper::new(file.into(), DocFileName::new(number, None), Some((value, prdoc)))
+		};
+		let mut wrappers = vec![
+			wrapper(2, "b/pr_2.prdoc", "beta"),
+			wrapper(1, "c/pr_1.prdoc", "Gamma"),
+			wrapper(3, "a/pr_3.prdoc", "Alpha"),
+			wrapper(1, "a/pr_1.prdoc", "Gamma"),
+		];
+		let order = |wrappers: &[DocFileWrapper]| {
+			wrappers
+				.iter()
+				.map(|w| w.file.to_string_lossy().into_owned())
+				.collect::<Vec<_>>()
+		};
 
-			(None, None, Some(list)) => {
-				log::debug!("Loading list from {:?}", list);
-				let (global_result, wrapper) = load_cmd.load_list(&list, dir)?;
-				(global_result, wrapper)
-			},
+		LoadCmd::sort(&mut wrappers, SortBy::Number, false);
+		assert_eq!(
+			vec!["a/pr_1.prdoc", "c/pr_1.prdoc", "b/pr_2.prdoc", "a/pr_3.prdoc"],
+			order(&wrappers)
+		);
 
-			(None, None, None) => {
-				log::debug!("Loading all files in folder {}", dir.display());
-				let (global_result, wrapper) = load_cmd.load_from_folder(dir)?;
-				(global_result, wrapper)
-			},
+		LoadCmd::sort(&mut wrappers, SortBy::Title, false);
+		assert_eq!(
+			vec!["a/pr_3.prdoc", "b/pr_2.prdoc", "a/pr_1.prdoc", "c/pr_1.prdoc"],
+			order(&wrappers)
+		);
+
+		LoadCmd::sort(&mut wrappers, SortBy::Path, true);
+		assert_eq!(
+			vec!["c/pr_1.prdoc", "b/pr_2.prdoc", "a/pr_3.prdoc", "a/pr_1.prdoc"],
+			order(&wrappers)
+		);
 
-			_ => unreachable!(),
-		})
+		assert_eq!(Ok(SortBy::Mtime), SortBy::from_str("mtime", false));
+		assert!(SortBy::from_str("size", false).is_err());
 	}
 }
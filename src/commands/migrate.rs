@@ -0,0 +1,280 @@
+//! Implementation of the migrate command. This command upgrades PRDoc files declaring an older
+//! version of the schema to the current one, using the [MIGRATIONS].
+
+use crate::{
+	config::PRDocConfig,
+	docfile::DocFile,
+	error::{PRdocLibError, Result},
+	migration::{self, Migration, MIGRATIONS},
+	schema::{Schema, SchemaSource, SchemaVersion},
+	utils::get_project_root,
+	yaml_edit::YamlEditor,
+};
+use regex::Regex;
+use serde::Serialize;
+use similar::TextDiff;
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::LazyLock,
+};
+
+/// Wrapper for the migrate command
+pub struct MigrateCmd {
+	pub(crate) schema: Schema,
+	pub(crate) migrations: Vec<Migration>,
+}
+
+/// Outcome of the migration of a file
+#[derive(Debug, Serialize)]
+pub struct MigrationResult {
+	/// The migrated file
+	pub file: PathBuf,
+
+	/// Version declared by the file before the migration
+	pub from: Option<SchemaVersion>,
+
+	/// Version declared by the file after the migration
+	pub to: Option<SchemaVersion>,
+
+	/// Description of the migrations that were applied
+	pub applied: Vec<&'static str>,
+
+	/// Whether the content of the file changed
+	pub changed: bool,
+
+	#[serde(skip)]
+	original: String,
+
+	#[serde(skip)]
+	migrated: String,
+}
+
+impl MigrationResult {
+	/// Return the content of the file after the migration
+	pub fn migrated(&self) -> &str {
+		&self.migrated
+	}
+
+	/// Return a unified diff of the changes
+	pub fn diff(&self) -> String {
+		let name = self.file.display().to_string();
+		TextDiff::from_lines(&self.original, &self.migrated)
+			.unified_diff()
+			.header(&name, &name)
+			.to_string()
+	}
+}
+
+impl MigrateCmd {
+	/// Create a new instance of the migrate command using the registered migrations
+	pub fn new(schema: Schema) -> Self {
+		Self { schema, migrations: MIGRATIONS.to_vec() }
+	}
+
+	/// Register additional migrations
+	pub fn with_migrations(mut self, migrations: impl IntoIterator<Item = Migration>) -> Self {
+		self.migrations.extend(migrations);
+		self
+	}
+
+	/// Migrate the `source` of a PRDoc to the version of the current schema. The `file` is only
+	/// used to report diagnostics. The result is validated against the current schema.
+	pub fn migrate_str(&self, file: &Path, source: &str) -> Result<MigrationResult> {
+		let target = self.schema.version().ok_or_else(|| {
+			PRdocLibError::MigrationFailed("the current schema does not declare a version".into())
+		})?;
+		let declared = SchemaVersion::declared_in(source);
+
+		let mut migrated = source.to_string();
+		let mut applied = vec![];
+
+		// Files not declaring a version are assumed to follow the current schema
+		if let Some((from, line)) = declared {
+			if from.major > target.major {
+				return Err(PRdocLibError::MigrationFailed(format!(
+					"{} declares the schema {from} which is newer than {target}",
+					file.display()
+				)));
+			}
+
+			let mut editor = YamlEditor::new(source)?;
+			for step in migration::path(&self.migrations, from.major, target.major)? {
+				log::debug!(
+					"{}: v{} -> v{}: {}",
+					file.display(),
+					step.from,
+					step.to,
+					step.description
+				);
+				(step.apply)(&mut editor)?;
+				applied.push(step.description);
+			}
+
+			migrated = set_declared_version(&editor.into_source(), line, target);
+		}
+
+		self.schema.validate_str(file, &migrated)?;
+
+		Ok(MigrationResult {
+			file: file.to_path_buf(),
+			from: declared.map(|(version, _)| version),
+			to: declared.map(|_| target),
+			applied,
+			changed: migrated != source,
+			original: source.to_string(),
+			migrated,
+		})
+	}
+
+	/// Migrate a file. The file is only rewritten if its content changed and `dry_run` is false.
+	pub fn migrate_file(&self, file: &Path, dry_run: bool) -> Result<MigrationResult> {
+		let source = fs::read_to_string(file)?;
+		let result = self.migrate_str(file, &source)?;
+
+		if result.changed && !dry_run {
+			fs::write(file, &result.migrated)?;
+		}
+		Ok(result)
+	}
+
+	/// Run of the migrate command: migrate a single `file` or all the files found in `dir`
+	pub fn run(
+		config: &PRDocConfig,
+		schema: Option<SchemaSource>,
+		dir: &PathBuf,
+		file: Option<PathBuf>,
+		dry_run: bool,
+	) -> Result<Vec<(PathBuf, Result<MigrationResult>)>> {
+		let repo_root = get_project_root()?;
+		let schema = config.load_schema(schema, &repo_root)?;
+		let migrate_cmd = MigrateCmd::new(schema);
+
+		let files: Vec<PathBuf> = match file {
+			Some(file) if file.is_relative() => vec![dir.join(file)],
+			Some(file) => vec![file],
+			None => DocFile::find(migrate_cmd.schema.clone(), dir, false)?.collect(),
+		};
+
+		Ok(files
+			.into_iter()
+			.map(|file| {
+				let result = migrate_cmd.migrate_file(&file, dry_run);
+				(file, result)
+			})
+			.collect())
+	}
+}
+
+/// Replace the version declared on the `line` (starting at 1) of the `source`
+fn set_declared_version(source: &str, line: usize, version: SchemaVersion) -> String {
+	static VERSION: LazyLock<Regex> =
+		LazyLock::new(|| Regex::new(r"v\d+(?:\.\d+){0,2}(\s*)$").unwrap());
+
+	source
+		.split_inclusive('\n')
+		.enumerate()
+		.map(|(index, text)| {
+			if index + 1 != line {
+				return text.to_string();
+			}
+			let (content, eol) = match text.strip_suffix('\n') {
+				Some(content) => (content, "\n"),
+				None => (text, ""),
+			};
+			format!("{}{eol}", VERSION.replace(content, format!("{version}${{1}}")))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test_migrate {
+	use super::*;
+	use crate::schema::BuiltinSchema;
+
+	const SOURCE: &str = r#"# Schema: Parity PR Documentation Schema (prdoc) v1.0.0
+# See doc at https://github.com/paritytech/prdoc
+
+title: Foobar
+
+doc:
+  - audience: Node Dev # keep me
+    description: &desc |
+      Some text
+  - audience: Runtime Dev
+    description: *desc
+
+migrations:
+  db: []
+  runtime: []
+
+crates: []
+
+host_functions: []
+"#;
+
+	/// A fictive v2 of the schema where the `Node Dev` audience was renamed `Node Operator`
+	fn migrate_cmd() -> MigrateCmd {
+		let mut json: serde_json::Value =
+			serde_json::from_str(BuiltinSchema::latest().content).unwrap();
+		json["version"]["major"] = 2.into();
+		let schema = Schema::from_json_str(&json.to_string()).unwrap();
+
+		fn rename_audience(editor: &mut YamlEditor) -> Result<()> {
+			let value = editor.value()?;
+			let count = value["doc"].as_sequence().map(|s| s.len()).unwrap_or_default();
+			for index in 0..count {
+				if value["doc"][index]["audience"] == "Node Dev" {
+					editor.set_scalar(&format!("/doc/{index}/audience"), "Node Operator")?;
+				}
+			}
+			Ok(())
+		}
+
+		MigrateCmd::new(schema).with_migrations([Migration {
+			from: 1,
+			to: 2,
+			description: "Rename the Node Dev audience",
+			apply: rename_audience,
+		}])
+	}
+
+	#[test]
+	fn test_migrate() {
+		let result = migrate_cmd().migrate_str(Path::new("pr_1.prdoc"), SOURCE).unwrap();
+
+		assert!(result.changed);
+		assert_eq!(Some(SchemaVersion::new(1, 0, 0)), result.from);
+		assert_eq!(Some(SchemaVersion::new(2, 0, 0)), result.to);
+		assert_eq!(vec!["Rename the Node Dev audience"], result.applied);
+
+		let migrated = result.migrated();
+		assert!(migrated.starts_with("# Schema: Parity PR Documentation Schema (prdoc) v2.0.0\n"));
+		assert!(migrated.contains("  - audience: Node Operator # keep me\n"));
+		assert!(migrated.contains("description: &desc |"));
+		assert!(migrated.contains("description: *desc"));
+
+		let diff = result.diff();
+		assert!(diff.contains("-  - audience: Node Dev # keep me\n"));
+		assert!(diff.contains("+  - audience: Node Operator # keep me\n"));
+	}
+
+	#[test]
+	fn test_up_to_date() {
+		let cmd = MigrateCmd::new(Schema::builtin("v1").unwrap());
+		let result = cmd.migrate_str(Path::new("pr_1.prdoc"), SOURCE).unwrap();
+		assert!(!result.changed);
+		assert!(result.applied.is_empty());
+		assert!(result.diff().is_empty());
+	}
+
+	#[test]
+	fn test_no_migration_path() {
+		let cmd = migrate_cmd();
+		let source = SOURCE.replace("v1.0.0", "v0.1.0");
+		assert!(matches!(
+			cmd.migrate_str(Path::new("pr_1.prdoc"), &source),
+			Err(PRdocLibError::MigrationFailed(_))
+		));
+	}
+}
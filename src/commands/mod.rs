@@ -4,5 +4,9 @@
 pub mod check;
 pub mod generate;
 pub mod load;
+pub mod migrate;
 pub mod scan;
+pub mod schema;
+pub mod semver_check;
+pub mod summary;
 pub mod version;
@@ -4,28 +4,21 @@
 //! potentially be PRDOC files. It does not check the validity of the files and the scanning is
 //! solely done based on the filenames
 
-use crate::{docfile::DocFile, schema::Schema};
+use crate::{docfile::DocFile, error::Result, schema::Schema};
 use std::{env, path::PathBuf};
 
 /// Wrapper to the scan command
 pub struct ScanCmd;
 
 impl ScanCmd {
-	/// Run of the scan command
-	pub fn run(schema: Schema, directories: Vec<PathBuf>, all: bool) -> Vec<PathBuf> {
-		let current_dir = env::current_dir().expect("Failed retrieving the current dir !");
-		log::debug!("Current dir: {}", current_dir.display());
+	/// Run of the scan command. An error is returned if one of the `directories` cannot be read.
+	pub fn run(schema: Schema, directories: Vec<PathBuf>, all: bool) -> Result<Vec<PathBuf>> {
+		log::debug!("Current dir: {}", env::current_dir()?.display());
 
-		directories
-			.iter()
-			.flat_map(|directory| {
-				if let Ok(dir) = DocFile::find(schema.clone(), directory, !all) {
-					dir.collect()
-				} else {
-					eprint!("Invalid directory: {}", directory.display());
-					vec![]
-				}
-			})
-			.collect()
+		let mut files = vec![];
+		for directory in &directories {
+			files.extend(DocFile::find(schema.clone(), directory, !all)?);
+		}
+		Ok(files)
 	}
 }
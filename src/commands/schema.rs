@@ -0,0 +1,63 @@
+//! Implementation of the schema command. This command lists and exports the schemas embedded in
+//! the cli.
+
+use crate::{
+	error::{self, PRdocLibError},
+	schema::{BuiltinSchema, BUILTIN_PREFIX, BUILTIN_SCHEMAS},
+};
+use serde::Serialize;
+
+/// Wrapper to the schema command
+pub struct SchemaCmd;
+
+/// Description of an embedded schema
+#[derive(Debug, Serialize)]
+pub struct SchemaInfo {
+	/// Name to use to refer to the schema, ie `builtin:v1`
+	pub name: String,
+
+	/// Version declared by the schema itself, ie `1.0.0`
+	pub version: Option<String>,
+
+	/// Title of the schema
+	pub title: Option<String>,
+
+	/// Whether this is the latest embedded schema
+	pub latest: bool,
+}
+
+impl From<&BuiltinSchema> for SchemaInfo {
+	fn from(builtin: &BuiltinSchema) -> Self {
+		let json: serde_json::Value =
+			serde_json::from_str(builtin.content).expect("Embedded schemas are valid JSON");
+		let version = json.get("version").map(|v| {
+			let part = |name: &str| v.get(name).and_then(|n| n.as_u64()).unwrap_or_default();
+			format!("{}.{}.{}", part("major"), part("minor"), part("patch"))
+		});
+		let title = json.get("title").and_then(|t| t.as_str()).map(|t| t.to_string());
+
+		Self { name: builtin.name(), version, title, latest: builtin == BuiltinSchema::latest() }
+	}
+}
+
+impl SchemaCmd {
+	/// List the embedded schemas
+	pub fn list() -> Vec<SchemaInfo> {
+		BUILTIN_SCHEMAS.iter().map(SchemaInfo::from).collect()
+	}
+
+	/// Return the content of the embedded schema matching `version` or the latest one if no
+	/// version is provided
+	pub fn export(version: Option<&str>) -> error::Result<&'static str> {
+		let builtin = match version {
+			Some(version) => {
+				let version = version.strip_prefix(BUILTIN_PREFIX).unwrap_or(version);
+				BuiltinSchema::get(version).ok_or_else(|| {
+					PRdocLibError::UnknownSchema(format!("{BUILTIN_PREFIX}{version}"))
+				})?
+			},
+			None => BuiltinSchema::latest(),
+		};
+		Ok(builtin.content)
+	}
+}
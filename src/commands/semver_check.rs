@@ -0,0 +1,310 @@
+//! Implementation of the semver-check command. This command compares the semver bumps declared in
+//! the PRDoc files with the versions of the crates found in the `Cargo.toml` files at two git
+//! revisions.
+
+use crate::{
+	docfile_wrapper::DocFileWrapper,
+	error::{PRdocLibError, Result},
+	git::Git,
+	model::SemverLevel,
+};
+use semver::Version;
+use serde::Serialize;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+};
+
+/// Wrapper for the semver-check command
+pub struct SemverCheckCmd {
+	git: Git,
+}
+
+/// A disagreement between the declared and the actual bump of a crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SemverIssue {
+	/// The version was bumped less than the highest bump declared in the PRDoc files
+	Underbumped,
+
+	/// The version was bumped but no PRDoc declares a change for the crate, either because the
+	/// crate is not listed, is listed without `semver` or with `no change`
+	Undeclared,
+}
+
+/// Declared and actual bump of a crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrateSemver {
+	/// Name of the crate
+	pub name: String,
+
+	/// Version at the base revision, `None` if the crate did not exist
+	pub from: Option<Version>,
+
+	/// Version at the compared revision, `None` if the crate does not exist anymore
+	pub to: Option<Version>,
+
+	/// The bump between both versions, if the crate exists at both revisions
+	pub actual: Option<SemverLevel>,
+
+	/// The highest bump declared in the PRDoc files
+	pub declared: Option<SemverLevel>,
+
+	/// The PRDoc files declaring a bump for the crate
+	pub prdocs: Vec<PathBuf>,
+
+	/// The problem found, if any
+	pub issue: Option<SemverIssue>,
+}
+
+/// Result of the semver-check command
+#[derive(Debug, Clone, Serialize)]
+pub struct SemverReport {
+	/// Base revision
+	pub from: String,
+
+	/// Compared revision
+	pub to: String,
+
+	/// The crates either declared in a PRDoc or whose version changed, ordered by name
+	pub crates: Vec<CrateSemver>,
+}
+
+impl SemverReport {
+	/// Return the crates with an issue
+	pub fn issues(&self) -> impl Iterator<Item = &CrateSemver> {
+		self.crates.iter().filter(|c| c.issue.is_some())
+	}
+}
+
+impl SemverCheckCmd {
+	/// Create a new instance of the command working on the `git` repository
+	pub fn new(git: Git) -> Self {
+		Self { git }
+	}
+
+	/// Return the version of each crate found in the repository at `rev`
+	pub fn versions(&self, rev: &str) -> Result<BTreeMap<String, Version>> {
+		let manifests: Vec<PathBuf> = self
+			.git
+			.ls_files(rev)?
+			.into_iter()
+			.filter(|path| path.file_name().is_some_and(|name| name == "Cargo.toml"))
+			.collect();
+		let contents = self.git.show_all(rev, &manifests)?;
+
+		let parsed: Vec<(PathBuf, toml::Value)> = manifests
+			.into_iter()
+			.zip(contents)
+			.filter_map(|(path, content)| {
+				let value =
+					toml::from_str(&content?).map_err(|e| log::warn!("{}: {e}", path.display()));
+				Some((path, value.ok()?))
+			})
+			.collect();
+
+		// Versions can be inherited from the root manifest of the workspace
+		let workspace_version = parsed
+			.iter()
+			.find(|(path, _)| path == &PathBuf::from("Cargo.toml"))
+			.and_then(|(_, manifest)| {
+				manifest.get("workspace")?.get("package")?.get("version")?.as_str()
+			})
+			.and_then(|v| Version::parse(v).ok());
+
+		let mut versions = BTreeMap::new();
+		for (path, manifest) in &parsed {
+			let Some(package) = manifest.get("package") else { continue };
+			let Some(name) = package.get("name").and_then(|n| n.as_str()) else { continue };
+
+			let version = match package.get("version") {
+				Some(toml::Value::String(version)) => Version::parse(version).map_err(|e| {
+					PRdocLibError::InvalidManifest(PathBuf::from(format!(
+						"{rev}:{}: {e}",
+						path.display()
+					)))
+				})?,
+				Some(toml::Value::Table(_)) => match &workspace_version {
+					Some(version) => version.clone(),
+					None => continue,
+				},
+				// Cargo defaults to 0.0.0 when the version is omitted
+				_ => Version::new(0, 0, 0),
+			};
+			versions.insert(name.to_string(), version);
+		}
+		Ok(versions)
+	}
+
+	/// Compare the bumps declared in the `prdocs` with the versions of the crates at `from` and
+	/// `to`
+	pub fn check(&self, from: &str, to: &str, prdocs: &[DocFileWrapper]) -> Result<SemverReport> {
+		let from_commit = self.git.rev_parse(from)?;
+		let to_commit = self.git.rev_parse(to)?;
+		let before = self.versions(&from_commit)?;
+		let after = self.versions(&to_commit)?;
+
+		let mut declared: BTreeMap<&str, (Option<SemverLevel>, Vec<PathBuf>)> = BTreeMap::new();
+		for wrapper in prdocs {
+			let Some(prdoc) = &wrapper.prdoc else { continue };
+			for krate in prdoc.crates.iter().filter(|krate| !krate.name.is_empty()) {
+				let entry = declared.entry(krate.name.as_str()).or_default();
+				entry.0 = entry.0.max(krate.semver);
+				entry.1.push(wrapper.file.clone());
+			}
+		}
+
+		let bumped = after.iter().filter(|(name, v)| before.get(*name).is_some_and(|b| b != *v));
+		let names: BTreeSet<&str> =
+			declared.keys().copied().chain(bumped.map(|(name, _)| name.as_str())).collect();
+
+		let crates = names
+			.into_iter()
+			.map(|name| {
+				let from = before.get(name).cloned();
+				let to = after.get(name).cloned();
+				let actual = match (&from, &to) {
+					(Some(from), Some(to)) => Some(bump_level(from, to)),
+					_ => None,
+				};
+				let (declared, prdocs) = declared.get(name).cloned().unwrap_or_default();
+
+				let issue = match (actual, declared) {
+					(Some(actual), Some(declared)) if actual < declared =>
+						Some(SemverIssue::Underbumped),
+					(Some(actual), None | Some(SemverLevel::NoChange))
+						if actual > SemverLevel::NoChange =>
+						Some(SemverIssue::Undeclared),
+					_ => None,
+				};
+
+				CrateSemver { name: name.to_string(), from, to, actual, declared, prdocs, issue }
+			})
+			.collect();
+
+		Ok(SemverReport { from: from.to_string(), to: to.to_string(), crates })
+	}
+}
+
+/// Return the semver level of the bump from `from` to `to`. As Cargo does, the first non zero
+/// component is considered as the major one: `0.1.0` to `0.2.0` is a major bump.
+pub fn bump_level(from: &Version, to: &Version) -> SemverLevel {
+	let components = |v: &Version| [v.major, v.minor, v.patch];
+	let (from, to) = (components(from), components(to));
+
+	match (0..3).find(|&i| from[i] != to[i]) {
+		None => SemverLevel::NoChange,
+		Some(changed) => {
+			let leading_zeros = from.iter().take_while(|&&c| c == 0).count().min(2);
+			// Components before the first non zero one of the base version are all breaking
+			match changed.saturating_sub(leading_zeros) {
+				0 => SemverLevel::Major,
+				1 => SemverLevel::Minor,
+				_ => SemverLevel::Patch,
+			}
+		},
+	}
+}
+
+#[cfg(test)]
+mod test_semver_check {
+	use super::*;
+	use crate::{doc_filename::DocFileName, schema::Schema, utils::TestDir};
+	use std::path::Path;
+
+	/// Create a git repository with a workspace of 5 crates and bump them in a second commit
+	fn repo() -> TestDir {
+		let root = TestDir::new("semver-check");
+		let write = |path: &str, content: &str| {
+			root.write(path, content);
+		};
+		let git = |args: &[&str]| root.git(args);
+		let manifest =
+			|name: &str, version: &str| format!("[package]\nname = \"{name}\"\n{version}\n");
+
+		write("Cargo.toml", "[workspace]\nmembers = [\"a\", \"b\", \"c\", \"e\", \"f\"]\n\n[workspace.package]\nversion = \"1.0.0\"\n");
+		write("a/Cargo.toml", &manifest("a", "version.workspace = true"));
+		write("b/Cargo.toml", &manifest("b", "version = \"0.1.0\""));
+		write("c/Cargo.toml", &manifest("c", "version = \"2.0.0\""));
+		write("e/Cargo.toml", &manifest("e", "version = \"1.0.0\""));
+		write("f/Cargo.toml", &manifest("f", "version = \"1.0.0\""));
+		git(&["init", "-q"]);
+		git(&["add", "."]);
+		git(&["commit", "-q", "-m", "Initial"]);
+		git(&["tag", "v1"]);
+
+		write("Cargo.toml", "[workspace]\nmembers = [\"a\", \"b\", \"c\", \"e\", \"f\"]\n\n[workspace.package]\nversion = \"1.1.0\"\n");
+		write("b/Cargo.toml", &manifest("b", "version = \"0.1.1\""));
+		write("c/Cargo.toml", &manifest("c", "version = \"2.0.1\""));
+		write("e/Cargo.toml", &manifest("e", "version = \"1.0.1\""));
+		write("f/Cargo.toml", &manifest("f", "version = \"1.1.0\""));
+		git(&["commit", "-q", "-am", "Bump"]);
+
+		write(
+			"prdoc/pr_1.prdoc",
+			"title: Foo\ndoc: [{audience: Node Dev, description: Foo}]\nmigrations: {db: [], runtime: []}\nhost_functions: []\ncrates:\n  - name: a\n    semver: major\n  - name: b\n    semver: minor\n  - name: d\n    semver: patch\n  - name: e\n    semver: no change\n  - name: f\n  - semver: major\n",
+		);
+		root
+	}
+
+	#[test]
+	fn test_check() {
+		let root = repo();
+		let file = root.join("prdoc/pr_1.prdoc");
+		let schema = Schema::builtin("v1").unwrap();
+		let wrapper = DocFileWrapper::new(
+			file.clone(),
+			DocFileName::try_from(&file).unwrap(),
+			Some(schema.load(&file).unwrap()),
+		);
+
+		let cmd = SemverCheckCmd::new(Git::new(&root));
+		let report = cmd.check("v1", "HEAD", &[wrapper]).unwrap();
+
+		let summary: Vec<_> = report
+			.crates
+			.iter()
+			.map(|c| (c.name.as_str(), c.actual, c.declared, c.issue))
+			.collect();
+		assert_eq!(
+			vec![
+				(
+					"a",
+					Some(SemverLevel::Minor),
+					Some(SemverLevel::Major),
+					Some(SemverIssue::Underbumped)
+				),
+				("b", Some(SemverLevel::Minor), Some(SemverLevel::Minor), None),
+				("c", Some(SemverLevel::Patch), None, Some(SemverIssue::Undeclared)),
+				("d", None, Some(SemverLevel::Patch), None),
+				(
+					"e",
+					Some(SemverLevel::Patch),
+					Some(SemverLevel::NoChange),
+					Some(SemverIssue::Undeclared)
+				),
+				("f", Some(SemverLevel::Minor), None, Some(SemverIssue::Undeclared)),
+			],
+			summary
+		);
+		assert_eq!(4, report.issues().count());
+		assert!(cmd.check("v0", "HEAD", &[]).is_err());
+		assert!(Path::new(&report.crates[0].prdocs[0]).ends_with("pr_1.prdoc"));
+	}
+
+	fn level(from: &str, to: &str) -> SemverLevel {
+		bump_level(&Version::parse(from).unwrap(), &Version::parse(to).unwrap())
+	}
+
+	#[test]
+	fn test_bump_level() {
+		assert_eq!(SemverLevel::NoChange, level("1.2.3", "1.2.3"));
+		assert_eq!(SemverLevel::Patch, level("1.2.3", "1.2.4"));
+		assert_eq!(SemverLevel::Minor, level("1.2.3", "1.3.0"));
+		assert_eq!(SemverLevel::Major, level("1.2.3", "2.0.0"));
+		assert_eq!(SemverLevel::Major, level("0.1.3", "0.2.0"));
+		assert_eq!(SemverLevel::Minor, level("0.1.3", "0.1.4"));
+		assert_eq!(SemverLevel::Major, level("0.0.3", "0.0.4"));
+		assert_eq!(SemverLevel::Major, level("0.9.0", "1.0.0"));
+	}
+}
@@ -0,0 +1,177 @@
+//! Implementation of the summary command. This command aggregates the `crates` of a set of PRDoc,
+//! typically the ones of a release, to tell the strongest semver level declared for each crate and
+//! the PRs causing it.
+
+use crate::{common::PRNumber, docfile_wrapper::DocFileWrapper, model::SemverLevel};
+use serde::Serialize;
+use std::{cmp::Reverse, collections::BTreeMap, fmt::Write};
+
+/// Wrapper for the summary command
+pub struct SummaryCmd;
+
+/// A note about a crate and the PR it comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrateNote {
+	/// The PR number
+	pub pr: PRNumber,
+
+	/// The note
+	pub note: String,
+}
+
+/// The changes of a crate across all the PRDoc
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrateSummary {
+	/// Name of the crate
+	pub name: String,
+
+	/// The strongest semver level declared, `None` if no PRDoc declares a level
+	pub semver: Option<SemverLevel>,
+
+	/// The PRs declaring the strongest level, sorted by number
+	pub prs: Vec<PRNumber>,
+
+	/// The notes of all the PRs listing the crate, sorted by PR number
+	pub notes: Vec<CrateNote>,
+}
+
+/// Result of the summary command
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+	/// The crates sorted from the strongest to the weakest semver level, then by name
+	pub crates: Vec<CrateSummary>,
+}
+
+impl SummaryCmd {
+	/// Aggregate the crates listed in the `prdocs`. The PRDoc that could not be loaded and the
+	/// crates without a name are ignored.
+	pub fn summarize(prdocs: &[DocFileWrapper]) -> Summary {
+		let mut crates: BTreeMap<&str, CrateSummary> = BTreeMap::new();
+		for wrapper in prdocs {
+			let Some(prdoc) = &wrapper.prdoc else { continue };
+			let pr = wrapper.doc_filename.number;
+
+			for krate in prdoc.crates.iter().filter(|krate| !krate.name.is_empty()) {
+				let summary = crates.entry(krate.name.as_str()).or_insert_with(|| CrateSummary {
+					name: krate.name.clone(),
+					semver: None,
+					prs: vec![],
+					notes: vec![],
+				});
+
+				if krate.semver > summary.semver {
+					summary.semver = krate.semver;
+					summary.prs.clear();
+				}
+				if krate.semver == summary.semver && !summary.prs.contains(&pr) {
+					summary.prs.push(pr);
+				}
+				if let Some(note) = &krate.note {
+					summary.notes.push(CrateNote { pr, note: note.trim().to_string() });
+				}
+			}
+		}
+
+		let mut crates: Vec<CrateSummary> = crates.into_values().collect();
+		for summary in &mut crates {
+			summary.prs.sort();
+			summary.notes.sort_by_key(|note| note.pr);
+		}
+		// The sort is stable so the crates of a same level stay sorted by name
+		crates.sort_by_key(|summary| Reverse(summary.semver));
+		Summary { crates }
+	}
+}
+
+impl Summary {
+	/// Human readable output: one line per crate followed by its notes
+	pub fn text(&self) -> String {
+		let mut text = String::new();
+		for krate in &self.crates {
+			let _ = writeln!(text, "{}\t{}\t{}", krate.name, level(krate.semver), prs(&krate.prs));
+			for note in &krate.notes {
+				let _ = writeln!(text, "\t#{}: {}", note.pr, note.note.replace('\n', " "));
+			}
+		}
+		text
+	}
+
+	/// A Markdown table with a row per crate, ready to be pasted in the release notes
+	pub fn markdown(&self) -> String {
+		let mut markdown = String::from("| Crate | Semver | PRs | Notes |\n|---|---|---|---|\n");
+		for krate in &self.crates {
+			let notes: Vec<String> = krate
+				.notes
+				.iter()
+				.map(|note| format!("#{}: {}", note.pr, escape_markdown(&note.note)))
+				.collect();
+			let _ = writeln!(
+				markdown,
+				"| `{}` | {} | {} | {} |",
+				krate.name,
+				level(krate.semver),
+				prs(&krate.prs),
+				notes.join("<br>")
+			);
+		}
+		markdown
+	}
+}
+
+fn level(level: Option<SemverLevel>) -> &'static str {
+	level.map(|level| level.as_str()).unwrap_or("n/a")
+}
+
+fn prs(prs: &[PRNumber]) -> String {
+	prs.iter().map(|pr| format!("#{pr}")).collect::<Vec<_>>().join(", ")
+}
+
+/// Escape the characters that would break a table cell
+fn escape_markdown(text: &str) -> String {
+	text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod test_summary {
+	use super::*;
+	use crate::{doc_filename::DocFileName, model::PrDoc};
+
+	fn wrapper(number: PRNumber, crates: &str) -> DocFileWrapper {
+		let value: serde_yaml::Value = serde_yaml::from_str(&format!("crates: {crates}")).unwrap();
+		let prdoc = PrDoc::try_from(&value).unwrap();
+		DocFileWrapper::new(
+			format!("pr_{number}.prdoc").into(),
+			DocFileName::new(number, None),
+			Some((value, prdoc)),
+		)
+	}
+
+	#[test]
+	fn test_summarize() {
+		let prdocs = [
+			wrapper(3, "[{name: a, semver: patch}, {name: b, semver: major, note: Removed x}]"),
+			wrapper(1, "[{name: a, semver: minor, note: \"New | api\"}, {name: c}]"),
+			wrapper(2, "[{name: a, semver: minor}, {name: d, semver: major}, {semver: major}]"),
+		];
+		let summary = SummaryCmd::summarize(&prdocs);
+
+		let crates: Vec<_> = summary
+			.crates
+			.iter()
+			.map(|c| (c.name.as_str(), c.semver, c.prs.clone()))
+			.collect();
+		assert_eq!(
+			vec![
+				("b", Some(SemverLevel::Major), vec![3]),
+				("d", Some(SemverLevel::Major), vec![2]),
+				("a", Some(SemverLevel::Minor), vec![1, 2]),
+				("c", None, vec![1]),
+			],
+			crates
+		);
+		assert_eq!(vec![CrateNote { pr: 1, note: "New | api".into() }], summary.crates[2].notes);
+
+		assert!(summary.text().starts_with("b\tmajor\t#3\n\t#3: Removed x\nd\tmajor\t#2\n"));
+		assert!(summary.markdown().contains("| `a` | minor | #1, #2 | #1: New \\| api |\n"));
+	}
+}
@@ -1,8 +1,18 @@
 //! PRDoc config
 
-use crate::{error::Result, utils::get_project_root};
+use crate::{
+	error::{PRdocLibError, Result},
+	lint::{LintLevel, Linter, DEFAULT_PLACEHOLDERS},
+	schema::{Schema, SchemaSource, BUILTIN_SCHEMAS},
+	utils::{default_jobs, get_project_root},
+	workspace::Workspace,
+};
 use serde::Deserialize;
-use std::{fs, path::PathBuf};
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+};
 
 const CONFIG_NAMES: &[&str] = &["prdoc.toml", ".prdoc.toml"];
 
@@ -22,8 +32,24 @@ pub mod env {
 pub struct PRDocConfig {
 	// /// Config version
 	// pub(crate) version: u16,
-	/// Path of the schema
-	pub(crate) schema: PathBuf,
+	/// The schema: either a path or an embedded schema such as `builtin:v1`.
+	/// Defaults to the latest embedded schema.
+	#[serde(default)]
+	pub(crate) schema: SchemaSource,
+
+	/// Older schemas still accepted for files declaring their version. The embedded schemas are
+	/// always supported.
+	#[serde(default)]
+	pub(crate) supported_schemas: Vec<SchemaSource>,
+
+	/// Level of the lint rules, ie `duplicate-crate = "warning"`. Rules that are not listed
+	/// use their default level.
+	#[serde(default)]
+	pub(crate) lints: BTreeMap<String, LintLevel>,
+
+	/// Patterns of the values considered as placeholders by the `template-placeholder` lint
+	#[serde(default = "default_placeholders")]
+	pub(crate) placeholders: Vec<String>,
 
 	/// Used for load, scan, check
 	pub prdoc_folders: Vec<PathBuf>,
@@ -33,6 +59,15 @@ pub struct PRDocConfig {
 
 	/// Path of the file to use as template
 	pub(crate) template: PathBuf,
+
+	/// Number of files validated in parallel by `check` and `load`. Defaults to the number of
+	/// CPUs.
+	#[serde(default)]
+	pub(crate) jobs: Option<usize>,
+}
+
+fn default_placeholders() -> Vec<String> {
+	DEFAULT_PLACEHOLDERS.iter().map(|p| p.to_string()).collect()
 }
 
 /// Wrapper struct for the `PRDocConfig`
@@ -44,15 +79,16 @@ impl Config {
 	/// - $PROJECT_ROOT/.prdoc.toml
 	/// - $PRDOC_CONFIG
 	pub fn get_config_file(config_file: Option<PathBuf>) -> Result<PathBuf> {
-		let root = get_project_root().expect("prdoc should run in a repo");
-
 		if let Some(config) = config_file {
-			if PathBuf::from(&config).exists() {
-				log::debug!("Found config in {config:?}");
-				return Ok(config);
+			if !config.exists() {
+				return Err(PRdocLibError::ConfigNotFound(config));
 			}
+			log::debug!("Found config in {config:?}");
+			return Ok(config);
 		}
 
+		let root = get_project_root()?;
+
 		for name in CONFIG_NAMES {
 			let candidate = root.join(name);
 			if candidate.exists() {
@@ -62,7 +98,7 @@ impl Config {
 		}
 
 		log::warn!("Config not found");
-		Err(crate::error::PRdocLibError::MissingConfig)
+		Err(PRdocLibError::MissingConfig)
 	}
 
 	/// Return a default config. This is used when no config was found or the config file is invalid
@@ -74,15 +110,11 @@ impl Config {
 	pub fn load(config_opts: Option<PathBuf>) -> Result<PRDocConfig> {
 		let config_file = Self::get_config_file(config_opts)?;
 		log::debug!("Loading config from {config_file:?}");
-		let str = match fs::read_to_string(config_file.clone()) {
-			Ok(s) => s,
-			Err(_) => Err(crate::error::PRdocLibError::InvalidConfig(config_file.clone()))?,
-		};
+		let str = fs::read_to_string(&config_file)
+			.map_err(|e| PRdocLibError::InvalidConfig(config_file.clone(), e.to_string()))?;
 
-		match toml::from_str(str.as_str()) {
-			Ok(c) => Ok(c),
-			Err(_e) => Err(crate::error::PRdocLibError::InvalidConfig(config_file))?,
-		}
+		toml::from_str(str.as_str())
+			.map_err(|e| PRdocLibError::InvalidConfig(config_file, e.to_string()))
 	}
 }
 
@@ -90,17 +122,79 @@ impl Default for PRDocConfig {
 	fn default() -> Self {
 		Self {
 			// version: 1,
-			schema: "prdoc/schema_user.json".into(),
+			schema: SchemaSource::default(),
+			supported_schemas: vec![],
+			lints: BTreeMap::new(),
+			placeholders: default_placeholders(),
 			prdoc_folders: vec!["prdoc".into()],
 			output_dir: "prdoc".into(),
 			template: "template.prdoc".into(),
+			jobs: None,
 		}
 	}
 }
 
 impl PRDocConfig {
-	/// Return the path of the schema
-	pub fn schema_path(&self) -> PathBuf {
-		self.schema.clone()
+	/// Return the source of the schema
+	pub fn schema(&self) -> &SchemaSource {
+		&self.schema
+	}
+
+	/// Return the number of files to validate in parallel
+	pub fn jobs(&self) -> usize {
+		self.jobs.filter(|jobs| *jobs > 0).unwrap_or_else(default_jobs)
+	}
+
+	/// Override the number of files validated in parallel, ie from the command line
+	pub fn with_jobs(mut self, jobs: Option<usize>) -> Self {
+		self.jobs = jobs.or(self.jobs);
+		self
+	}
+
+	/// Return the level of the lint rules set in the config
+	pub fn lints(&self) -> &BTreeMap<String, LintLevel> {
+		&self.lints
+	}
+
+	/// Create the linter configured with the lint levels, the template and the placeholders.
+	/// A relative template is resolved from `root`, where the workspace manifest is also expected.
+	pub fn linter(&self, root: &Path) -> Result<Linter> {
+		let template = root.join(&self.template);
+		let template = match fs::read_to_string(&template) {
+			Ok(content) => Some(content),
+			Err(e) => {
+				log::debug!("Template {} not available: {e}", template.display());
+				None
+			},
+		};
+		let linter = Linter::new(self.lints.clone())
+			.with_template(template.as_deref(), &self.placeholders)?;
+
+		// Parsing the manifests is only worth it when the crates need to be checked
+		if linter.is_enabled("unknown-crate") || linter.is_enabled("unpublished-crate") {
+			Ok(linter.with_workspace(Workspace::load(root)?))
+		} else {
+			Ok(linter)
+		}
+	}
+
+	/// Load the schema to validate files with. The `schema` overrides the one from the config.
+	/// Both the schemas listed in `supported_schemas` and the embedded schemas are registered as
+	/// supported versions. Relative paths are resolved from `root`.
+	pub fn load_schema(&self, schema: Option<SchemaSource>, root: &Path) -> Result<Schema> {
+		let source = schema.unwrap_or_else(|| self.schema.clone());
+		log::info!("Using schema: {source}");
+		let current = source.load(root)?;
+
+		let builtins = BUILTIN_SCHEMAS.iter().map(|b| SchemaSource::Builtin(b.version.to_string()));
+		let supported = self
+			.supported_schemas
+			.iter()
+			.cloned()
+			.chain(builtins)
+			.map(|source| source.load(root))
+			.collect::<Result<Vec<_>>>()?;
+
+		Ok(current.with_supported(supported))
 	}
 }
@@ -0,0 +1,436 @@
+//! Diagnostics reported when checking PRDoc files.
+//!
+//! A [Diagnostic] points at the exact location of a problem in a PRDoc file and renders in a
+//! similar fashion to the diagnostics of `rustc`:
+//!
+//! ```text
+//! error[schema/required]: This property is required: `title`
+//!  --> prdoc/pr_1234.prdoc:1:1
+//!   |
+//! 1 | doc:
+//!   | ^^^^
+//!   = help: Title for the PR. This is what will show up in the documentation
+//! ```
+
+use serde::Serialize;
+use std::{
+	fmt::Display,
+	path::{Path, PathBuf},
+};
+use valico::json_schema::ValidationState;
+
+use crate::source_map::{Position, Range, SourceMap};
+
+/// Severity of a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+	/// The file is not valid
+	Error,
+
+	/// The file is valid but something deserves attention
+	Warning,
+}
+
+impl Display for Severity {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Severity::Error => "error",
+			Severity::Warning => "warning",
+		})
+	}
+}
+
+/// A problem found in a PRDoc file
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Diagnostic {
+	/// Severity of the problem
+	pub severity: Severity,
+
+	/// Identifier of the rule that failed, ie `schema/required`
+	pub code: String,
+
+	/// Description of the problem
+	pub message: String,
+
+	/// The file where the problem was found
+	pub file: PathBuf,
+
+	/// Path of the node related to the problem, ie `/doc/0/audience`
+	pub path: String,
+
+	/// Location of the problem in the file, if it could be found
+	pub range: Option<Range>,
+
+	/// The line of the source the range starts on
+	pub snippet: Option<String>,
+
+	/// An optional hint helping to fix the problem
+	pub hint: Option<String>,
+}
+
+impl Diagnostic {
+	/// Create a new diagnostic for the node at `path`. The location is resolved using the
+	/// `source` of the file. If the node does not exist, the closest existing parent is used.
+	pub fn new(
+		severity: Severity,
+		code: impl Into<String>,
+		message: impl Into<String>,
+		file: &Path,
+		path: &str,
+		source: &str,
+		source_map: Option<&SourceMap>,
+	) -> Self {
+		let range = source_map.and_then(|map| map.closest(path)).map(|(_, node)| {
+			// Empty values such as `doc:` are better represented by their key
+			match node.key {
+				Some(key) if node.value.start == node.value.end => key,
+				_ => node.value,
+			}
+		});
+
+		Self {
+			severity,
+			code: code.into(),
+			message: message.into(),
+			file: file.to_path_buf(),
+			path: path.to_string(),
+			range,
+			snippet: Self::snippet(source, range),
+			hint: None,
+		}
+	}
+
+	fn snippet(source: &str, range: Option<Range>) -> Option<String> {
+		range
+			.and_then(|r| source.lines().nth(r.start.line.saturating_sub(1)))
+			.map(|line| line.to_string())
+	}
+
+	/// Point the diagnostic at a whole line of the source, `line` starting at 1
+	pub fn at_line(mut self, source: &str, line: usize) -> Self {
+		let offset: usize = source.split_inclusive('\n').take(line - 1).map(|l| l.len()).sum();
+		let text = source.lines().nth(line - 1).unwrap_or_default();
+		let start = Position { line, column: 1, offset };
+		let end = Position { line, column: text.chars().count() + 1, offset: offset + text.len() };
+		self.range = Some(Range { start, end });
+		self.snippet = Some(text.to_string());
+		self
+	}
+
+	/// Point the diagnostic at the key of the node instead of its value
+	fn at_key(mut self, source: &str, source_map: Option<&SourceMap>) -> Self {
+		if let Some(key) = source_map.and_then(|map| map.get(&self.path)).and_then(|n| n.key) {
+			self.range = Some(key);
+			self.snippet = Self::snippet(source, self.range);
+		}
+		self
+	}
+
+	/// Attach a hint to the diagnostic
+	pub fn with_hint(mut self, hint: Option<String>) -> Self {
+		self.hint = hint;
+		self
+	}
+
+	/// Convert the errors of a schema validation into diagnostics
+	pub fn from_validation(
+		file: &Path,
+		source: &str,
+		schema: &serde_json::Value,
+		validation: &ValidationState,
+	) -> Vec<Self> {
+		let source_map = SourceMap::parse(source);
+
+		let mut diagnostics: Vec<Self> = validation
+			.errors
+			.iter()
+			.map(|error| {
+				let mut path = error.get_path().to_string();
+				let mut message = error.get_title().to_string();
+				let mut on_key = false;
+
+				match error.get_code() {
+					// The path of a missing property is the property itself
+					"required" => {
+						let property = path.rsplit('/').next().unwrap_or_default();
+						message = format!("{message}: `{property}`");
+					},
+					// The offending key is only mentioned in the detail
+					"properties" => {
+						let detail = error.get_detail().unwrap_or_default();
+						if let Some(key) = detail
+							.strip_prefix("Additional property '")
+							.and_then(|s| s.strip_suffix("' is not allowed"))
+						{
+							path = format!("{path}/{key}");
+							on_key = true;
+						}
+						message = detail.to_string();
+					},
+					_ =>
+						if let Some(detail) = error.get_detail() {
+							message = format!("{message}: {detail}");
+						},
+				}
+
+				if let Some(allowed) = allowed_values(schema, &path) {
+					message = format!("{message}. Expected one of: {allowed}");
+				}
+
+				let diagnostic = Self::new(
+					Severity::Error,
+					format!("schema/{}", error.get_code()),
+					message,
+					file,
+					&path,
+					source,
+					source_map.as_ref(),
+				)
+				.with_hint(describe(schema, &path));
+
+				if on_key {
+					diagnostic.at_key(source, source_map.as_ref())
+				} else {
+					diagnostic
+				}
+			})
+			.collect();
+
+		diagnostics.sort_by_key(|d| d.range.map(|r| r.start));
+		diagnostics
+	}
+
+	/// Report the properties of a valid document that the schema marks as `deprecated`. The
+	/// validation itself ignores the annotation.
+	pub fn from_deprecated(
+		file: &Path,
+		source: &str,
+		schema: &serde_json::Value,
+		doc: &serde_json::Value,
+	) -> Vec<Self> {
+		let mut paths = vec![];
+		collect_paths(doc, String::new(), &mut paths);
+
+		let deprecated: Vec<String> = paths
+			.into_iter()
+			.filter(|path| {
+				definition(schema, path).is_some_and(|node| {
+					[node, resolve(schema, node)]
+						.iter()
+						.any(|n| n.get("deprecated").and_then(|d| d.as_bool()) == Some(true))
+				})
+			})
+			.collect();
+		if deprecated.is_empty() {
+			return vec![];
+		}
+
+		let source_map = SourceMap::parse(source);
+		deprecated
+			.iter()
+			.map(|path| {
+				let property = path.rsplit('/').next().unwrap_or_default();
+				Self::new(
+					Severity::Warning,
+					"schema/deprecated",
+					format!("`{property}` is deprecated"),
+					file,
+					path,
+					source,
+					source_map.as_ref(),
+				)
+				.with_hint(describe(schema, path))
+			})
+			.collect()
+	}
+}
+
+/// Collect the paths of all the nodes of a document, ie `/crates/0/name`
+fn collect_paths(value: &serde_json::Value, path: String, paths: &mut Vec<String>) {
+	match value {
+		serde_json::Value::Object(map) =>
+			for (key, value) in map {
+				let child = format!("{path}/{key}");
+				paths.push(child.clone());
+				collect_paths(value, child, paths);
+			},
+		serde_json::Value::Array(items) =>
+			for (index, value) in items.iter().enumerate() {
+				let child = format!("{path}/{index}");
+				paths.push(child.clone());
+				collect_paths(value, child, paths);
+			},
+		_ => {},
+	}
+}
+
+impl Display for Diagnostic {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "{}[{}]: {}", self.severity, self.code, self.message)?;
+
+		let Some(range) = self.range else {
+			writeln!(f, " --> {}", self.file.display())?;
+			if let Some(hint) = &self.hint {
+				writeln!(f, "  = help: {}", hint.trim().replace('\n', " "))?;
+			}
+			return Ok(());
+		};
+
+		let line = range.start.line.to_string();
+		let gutter = " ".repeat(line.len());
+		writeln!(f, "{gutter}--> {}:{}:{}", self.file.display(), line, range.start.column)?;
+		writeln!(f, "{gutter} |")?;
+
+		if let Some(snippet) = &self.snippet {
+			let width = if range.end.line == range.start.line {
+				range.end.column.saturating_sub(range.start.column).max(1)
+			} else {
+				snippet.chars().count().saturating_sub(range.start.column - 1).max(1)
+			};
+			let padding = " ".repeat(range.start.column - 1);
+			writeln!(f, "{line} | {snippet}")?;
+			writeln!(f, "{gutter} | {padding}{}", "^".repeat(width))?;
+		}
+
+		if let Some(hint) = &self.hint {
+			writeln!(f, "{gutter} = help: {}", hint.trim().replace('\n', " "))?;
+		}
+		Ok(())
+	}
+}
+
+/// Resolve a local reference such as `#/$defs/doc`
+fn resolve<'a>(root: &'a serde_json::Value, node: &'a serde_json::Value) -> &'a serde_json::Value {
+	match node.get("$ref").and_then(|r| r.as_str()).and_then(|r| r.strip_prefix('#')) {
+		Some(pointer) => root.pointer(pointer).unwrap_or(node),
+		None => node,
+	}
+}
+
+/// Find the definition of the property at `path` in the schema
+fn definition<'a>(schema: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+	let mut node = schema;
+	for segment in path.split('/').filter(|s| !s.is_empty()) {
+		let current = resolve(schema, node);
+		node = if segment.parse::<usize>().is_ok() && current.get("items").is_some() {
+			current.get("items")?
+		} else {
+			current.get("properties")?.get(segment)?
+		};
+	}
+	Some(node)
+}
+
+/// Return the description of the property at `path`, if the schema provides one
+fn describe(schema: &serde_json::Value, path: &str) -> Option<String> {
+	let node = definition(schema, path)?;
+	node.get("description")
+		.or_else(|| resolve(schema, node).get("description"))
+		.and_then(|d| d.as_str())
+		.map(|d| d.to_string())
+}
+
+/// Return the list of allowed values for the property at `path`, if it is restricted to a set of
+/// values
+fn allowed_values(schema: &serde_json::Value, path: &str) -> Option<String> {
+	let node = resolve(schema, definition(schema, path)?);
+	let values: Vec<&serde_json::Value> = if let Some(values) = node.get("enum") {
+		values.as_array()?.iter().collect()
+	} else {
+		node.get("oneOf")?.as_array()?.iter().filter_map(|v| v.get("const")).collect()
+	};
+
+	if values.is_empty() {
+		None
+	} else {
+		Some(values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "))
+	}
+}
+
+#[cfg(test)]
+mod test_diagnostic {
+	use super::*;
+
+	fn schema() -> serde_json::Value {
+		let content = std::fs::read_to_string("./tests/data/sample_schema.json").unwrap();
+		serde_json::from_str(&content).unwrap()
+	}
+
+	#[test]
+	fn test_deprecated() {
+		let schema = serde_json::json!({
+			"properties": {
+				"title": { "type": "string" },
+				"labels": { "$ref": "#/$defs/labels" },
+				"crates": {
+					"items": {
+						"properties": {
+							"bump": { "deprecated": true, "description": "Use `semver` instead" }
+						}
+					}
+				}
+			},
+			"$defs": { "labels": { "type": "array", "deprecated": true } }
+		});
+		let source = "title: Foo\nlabels: []\ncrates:\n  - name: foo\n    bump: major\n";
+		let doc = serde_json::to_value(serde_yaml::from_str::<serde_yaml::Value>(source).unwrap())
+			.unwrap();
+
+		let diagnostics =
+			Diagnostic::from_deprecated(Path::new("pr_1.prdoc"), source, &schema, &doc);
+		let found: Vec<_> = diagnostics
+			.iter()
+			.map(|d| (d.severity, d.path.as_str(), d.range.unwrap().start.line))
+			.collect();
+		assert_eq!(
+			vec![(Severity::Warning, "/crates/0/bump", 5), (Severity::Warning, "/labels", 2)],
+			found
+		);
+		assert_eq!("`bump` is deprecated", diagnostics[0].message);
+		assert_eq!(Some("Use `semver` instead".to_string()), diagnostics[0].hint);
+	}
+
+	#[test]
+	fn test_describe() {
+		let schema = schema();
+		assert_eq!(
+			Some("Title for the PR. This is what will show up in the documentation".to_string()),
+			describe(&schema, "/title")
+		);
+		assert_eq!(Some("The selected audience".to_string()), describe(&schema, "/doc/0/audience"));
+		assert_eq!(None, describe(&schema, "/crates/0/name"));
+	}
+
+	#[test]
+	fn test_allowed_values() {
+		let schema = schema();
+		assert_eq!(
+			Some(r#""major", "minor", "patch", "no change""#.to_string()),
+			allowed_values(&schema, "/crates/1/semver")
+		);
+		assert!(allowed_values(&schema, "/doc/0/audience").unwrap().contains("\"Runtime User\""));
+	}
+
+	#[test]
+	fn test_render() {
+		let source = "title: Foo\ndoc:\n  - audience: Foo\n";
+		let map = SourceMap::parse(source);
+		let diagnostic = Diagnostic::new(
+			Severity::Error,
+			"schema/oneOf",
+			"OneOf conditions are not met",
+			Path::new("pr_1.prdoc"),
+			"/doc/0/audience",
+			source,
+			map.as_ref(),
+		)
+		.with_hint(Some("The selected audience".into()));
+
+		assert_eq!(
+			"error[schema/oneOf]: OneOf conditions are not met\n --> pr_1.prdoc:3:15\n  |\n3 |   - \
+			 audience: Foo\n  |               ^^^\n  = help: The selected audience\n",
+			diagnostic.to_string()
+		);
+	}
+}
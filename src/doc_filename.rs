@@ -7,12 +7,14 @@ use std::{
 	fmt::Display,
 	path::{Path, PathBuf},
 	str::FromStr,
+	sync::LazyLock,
 };
 
 use crate::{
 	common::PRNumber,
 	error::{self, PRdocLibError},
 	title::Title,
+	utils::read_dir,
 };
 
 /// Helps to build and check filenames for prdoc
@@ -70,57 +72,145 @@ impl DocFileName {
 		}
 	}
 
-	/// Search for a PR Doc in a given folder and matching the args
+	/// Explain which part of `pr_<number>[_<title>].prdoc` is wrong in `filename`. `None` is
+	/// returned if the filename is valid.
+	pub fn explain<P: AsRef<Path>>(filename: P) -> Option<String> {
+		let Some(name) = filename.as_ref().file_name().and_then(|name| name.to_str()) else {
+			return Some("the filename is missing or not valid UTF-8".into());
+		};
+		let Some(stem) = name.strip_suffix(".prdoc") else {
+			return Some("the extension must be `.prdoc`".into());
+		};
+		let Some(rest) = stem.strip_prefix("pr_") else {
+			return Some(if stem.to_lowercase().starts_with("pr_") {
+				"the `pr_` prefix must be lowercase".into()
+			} else {
+				"the filename must start with `pr_`".into()
+			});
+		};
+		if !rest.starts_with(|c: char| c.is_ascii_digit()) {
+			return Some("`pr_` must be followed by the PR number".into());
+		}
+		None
+	}
+
+	/// Return the formatting problems of a valid `filename`, such as spaces or separators at the
+	/// edges of the title
+	pub fn format_issues<P: AsRef<Path>>(filename: P) -> Vec<String> {
+		let path = filename.as_ref().to_path_buf();
+		let Ok(parsed) = Self::try_from(&path) else { return vec![] };
+		let name = path.file_name().unwrap_or_default().to_string_lossy();
+		let title = parsed.title.map(|t| t.0).unwrap_or_default();
+		let is_separator = |c: char| c == '_' || c == '-' || c.is_whitespace();
+
+		let mut issues = vec![];
+		if name.contains(char::is_whitespace) {
+			issues.push("the filename contains spaces".to_string());
+		}
+		match title.strip_prefix('_') {
+			_ if title.is_empty() => {},
+			None => issues.push("the title must be separated from the number by `_`".into()),
+			Some("") => issues.push("the filename ends with a separator".into()),
+			Some(slug) if slug.starts_with(is_separator) || slug.ends_with(is_separator) =>
+				issues.push(format!("the title `{slug}` starts or ends with a separator")),
+			Some(_) => {},
+		}
+		issues
+	}
+
+	/// Return the part of a PRDoc split in several files, ie `2` for `pr_1234_part2.prdoc` or
+	/// `pr_1234_part2_some_title.prdoc`
+	pub fn part(&self) -> Option<u32> {
+		static PART: LazyLock<Regex> =
+			LazyLock::new(|| Regex::new(r"^_part(?<part>\d+)(_.*)?$").unwrap());
+		let title = self.title.as_ref()?;
+		PART.captures(title.as_ref())?.name("part")?.as_str().parse().ok()
+	}
+
+	/// Search for a PR Doc in a given folder and matching the args. An error is returned if
+	/// several files match, see [find_all](Self::find_all) to get all of them.
 	pub fn find(
 		number: PRNumber,
 		title: Option<String>,
 		directory: &PathBuf,
 	) -> error::Result<PathBuf> {
 		if title.is_some() {
-			todo!("Searching by Number + Title is not implemented yet, open an issue if there is a need.");
+			return Err(PRdocLibError::InvalidArguments(
+				"searching by number and title is not supported, open an issue if there is a need"
+					.into(),
+			));
 		}
 
-		// We search for matching patterns and capture the `number` group
-		let re: Regex = Self::get_regex();
+		let mut hits = Self::find_all(number, std::slice::from_ref(directory))?;
+		match hits.len() {
+			0 => Err(PRdocLibError::NumberNotFound(number)),
+			1 => Ok(hits.remove(0)),
+			_ => Err(PRdocLibError::DuplicatePRDoc(number, Self::list(&hits))),
+		}
+	}
 
-		let hit_maybe = std::fs::read_dir(directory)?.find_map(|entry| match entry {
-			Ok(candidate) => {
-				// First we exclude anything that is not a file
-				let metadata = std::fs::metadata(candidate.path()).unwrap();
-				if !metadata.is_file() {
-					return None;
-				}
-
-				// Fetch the file name
-				let fname = candidate.file_name();
-				let filename = fname.to_str().unwrap_or_default();
-
-				// We capture numbers first
-				let number_capture = re.captures(filename).and_then(|cap| {
-					cap.name("number").map(|n| {
-						let s = n.as_str();
-						let my_num: PRNumber = s.parse().unwrap();
-						my_num
-					})
-				});
-
-				// Then check if the number we got matches.
-				// It is required to do this so we also find `pr_000...` when looking for
-				// PR #0
-				if number_capture.is_some_and(|value| value == number) {
-					Some(PathBuf::from(&directory).join(filename))
-				} else {
-					None
-				}
-			},
-			Err(_e) => None,
-		});
-
-		if let Some(hit) = hit_maybe {
-			Ok(hit)
-		} else {
-			Err(PRdocLibError::NumberNotFound(number))
+	/// Search for all the PR Docs of the PR `number` in the `directories`. The files are returned
+	/// in the order of the directories then sorted by name.
+	pub fn find_all(number: PRNumber, directories: &[PathBuf]) -> error::Result<Vec<PathBuf>> {
+		let mut hits = vec![];
+
+		for directory in directories {
+			let mut dir_hits: Vec<PathBuf> = read_dir(directory)?
+				.filter_map(|entry| entry.ok())
+				.map(|entry| entry.path())
+				.filter(|path| path.is_file())
+				// Comparing the parsed numbers also finds `pr_000...` when looking for PR #0
+				.filter(|path| Self::try_from(path).is_ok_and(|f| f.number == number))
+				.collect();
+			dir_hits.sort();
+			hits.extend(dir_hits);
 		}
+
+		Ok(hits)
+	}
+
+	/// Check that the `files` found for the PR `number`, ie using [find_all](Self::find_all), can
+	/// coexist. A PR can only have several PRDoc files if all of them are distinct parts named
+	/// `pr_<number>_part<N>[_<title>].prdoc`, N going from 1 to the number of parts.
+	pub fn check_parts(number: PRNumber, files: &[PathBuf]) -> error::Result<()> {
+		// A file listed several times is not its own duplicate
+		let mut files = files.to_vec();
+		files.sort();
+		files.dedup();
+		let files = files.as_slice();
+
+		let parts: Vec<Option<u32>> = files
+			.iter()
+			.map(|file| Self::try_from(file).ok().and_then(|f| f.part()))
+			.collect();
+
+		// A single file does not need to be a part, but a part alone means others are missing
+		if parts.is_empty() || parts == [None] {
+			return Ok(());
+		}
+		if parts.iter().any(|part| part.is_none()) {
+			return Err(PRdocLibError::DuplicatePRDoc(number, Self::list(files)));
+		}
+
+		let mut parts: Vec<u32> = parts.into_iter().flatten().collect();
+		parts.sort();
+		if parts[0] == 0 {
+			return Err(PRdocLibError::InvalidParts(number, "the parts start at 1".into()));
+		}
+		if let Some(part) = parts.windows(2).find(|w| w[0] == w[1]).map(|w| w[0]) {
+			let reason = format!("the part {part} exists several times: {}", Self::list(files));
+			return Err(PRdocLibError::InvalidParts(number, reason));
+		}
+		if let Some((missing, _)) = (1..).zip(&parts).find(|(expected, part)| expected != *part) {
+			let reason = format!("the part {missing} is missing: {}", Self::list(files));
+			return Err(PRdocLibError::InvalidParts(number, reason));
+		}
+
+		Ok(())
+	}
+
+	fn list(files: &[PathBuf]) -> String {
+		files.iter().map(|f| f.display().to_string()).collect::<Vec<_>>().join(", ")
 	}
 }
 
@@ -151,10 +241,15 @@ impl TryFrom<&PathBuf> for DocFileName {
 		let file = p.file_name().ok_or(PRdocLibError::InvalidFilename(p.clone()))?;
 		let filename = file.to_str().ok_or(PRdocLibError::InvalidFilename(p.clone()))?;
 
-		let number = re.captures(filename).and_then(|cap| {
-			cap.name("number")
-				.map(|n| n.as_str().parse().expect("The regexp captures numbers"))
-		});
+		// The regexp captures digits but the number may not fit in a PRNumber
+		let number = match re.captures(filename).and_then(|cap| cap.name("number")) {
+			Some(n) => Some(
+				n.as_str()
+					.parse::<PRNumber>()
+					.map_err(|_| PRdocLibError::InvalidFilename(p.clone()))?,
+			),
+			None => None,
+		};
 
 		let title: Option<Title> = re
 			.captures(filename)
@@ -180,6 +275,7 @@ impl TryFrom<&PathBuf> for DocFileName {
 #[cfg(test)]
 mod test_doc_file_name {
 	use super::*;
+	use crate::utils::TestDir;
 
 	#[test]
 	fn test_valid_names() {
@@ -206,6 +302,100 @@ mod test_doc_file_name {
 		);
 	}
 
+	#[test]
+	fn test_explain() {
+		assert_eq!(None, DocFileName::explain("pr_123_foo.prdoc"));
+		assert_eq!(
+			Some("the extension must be `.prdoc`".to_string()),
+			DocFileName::explain("pr_123.yml")
+		);
+		assert!(DocFileName::explain("PR_123.prdoc").unwrap().contains("lowercase"));
+		assert!(DocFileName::explain("123.prdoc").unwrap().contains("start with `pr_`"));
+		assert!(DocFileName::explain("pr_foo.prdoc").unwrap().contains("PR number"));
+	}
+
+	#[test]
+	fn test_format_issues() {
+		assert!(DocFileName::format_issues("pr_123_foo-bar.prdoc").is_empty());
+		assert!(DocFileName::format_issues("pr_123.prdoc").is_empty());
+		assert_eq!(
+			vec!["the filename ends with a separator"],
+			DocFileName::format_issues("pr_1238_.prdoc")
+		);
+		assert_eq!(
+			vec![
+				"the filename contains spaces",
+				"the title must be separated from the number by `_`"
+			],
+			DocFileName::format_issues("pr_1235 - junk.prdoc")
+		);
+		assert_eq!(
+			vec!["the title `foo_` starts or ends with a separator"],
+			DocFileName::format_issues("pr_1_foo_.prdoc")
+		);
+	}
+
+	#[test]
+	fn test_find_all() {
+		let dirs = [PathBuf::from("./tests/data/all"), PathBuf::from("./tests/data/some")];
+		assert_eq!(
+			vec![
+				PathBuf::from("./tests/data/all/pr_1237.prdoc"),
+				PathBuf::from("./tests/data/some/pr_1237.prdoc")
+			],
+			DocFileName::find_all(1237, &dirs).unwrap()
+		);
+		assert!(DocFileName::find_all(9999, &dirs).unwrap().is_empty());
+		assert!(DocFileName::find(1237, None, &dirs[0]).is_ok());
+	}
+
+	#[test]
+	fn test_parts() {
+		let part = |name: &str| DocFileName::try_from(&PathBuf::from(name)).unwrap().part();
+		assert_eq!(Some(1), part("pr_12_part1.prdoc"));
+		assert_eq!(Some(2), part("pr_12_part2_some_title.prdoc"));
+		assert_eq!(None, part("pr_12_partial.prdoc"));
+		assert_eq!(None, part("pr_12.prdoc"));
+
+		let check = |names: &[&str]| {
+			let files: Vec<PathBuf> = names.iter().map(PathBuf::from).collect();
+			DocFileName::check_parts(12, &files)
+		};
+		assert!(check(&["pr_12.prdoc"]).is_ok());
+		assert!(check(&["pr_12_part1.prdoc", "pr_12_part2_foo.prdoc"]).is_ok());
+		assert!(matches!(
+			check(&["pr_12.prdoc", "pr_12_.prdoc"]),
+			Err(PRdocLibError::DuplicatePRDoc(12, _))
+		));
+		assert!(check(&["pr_12.prdoc", "pr_12_part1.prdoc"]).is_err());
+		assert!(check(&["pr_12_part1.prdoc", "pr_12_part1_foo.prdoc"]).is_err());
+		assert!(check(&["pr_12_part0.prdoc", "pr_12_part1.prdoc"]).is_err());
+		assert!(check(&["pr_12_part1.prdoc"]).is_ok());
+		assert!(check(&["pr_12.prdoc", "pr_12.prdoc"]).is_ok());
+		assert!(check(&["pr_12_part2_foo.prdoc", "pr_12_part1.prdoc", "pr_12_part3.prdoc"]).is_ok());
+		assert!(matches!(
+			check(&["pr_12_part1.prdoc", "pr_12_part3.prdoc"]),
+			Err(PRdocLibError::InvalidParts(12, reason)) if reason.contains("part 2 is missing")
+		));
+		assert!(matches!(
+			check(&["pr_12_part2.prdoc"]),
+			Err(PRdocLibError::InvalidParts(12, reason)) if reason.contains("part 1 is missing")
+		));
+	}
+
+	#[test]
+	fn test_number_overflow() {
+		let file = PathBuf::from("pr_70000.prdoc");
+		assert!(
+			matches!(DocFileName::try_from(&file), Err(PRdocLibError::InvalidFilename(f)) if f == file)
+		);
+
+		let dir = TestDir::new("doc-filename-overflow");
+		dir.write("pr_70000.prdoc", "title: Foo\n");
+		let one = dir.write("pr_1.prdoc", "title: Foo\n");
+		assert_eq!(vec![one], DocFileName::find_all(1, &[dir.to_path_buf()]).unwrap());
+	}
+
 	#[test]
 	fn test_from_pathbuf() {
 		let dfn = DocFileName::try_from(&PathBuf::from(
@@ -5,7 +5,12 @@ use serde_yaml::Value;
 use std::{fs, path::PathBuf};
 
 use crate::{
-	common::PRNumber, doc_filename::DocFileName, error, schema::Schema, utils::get_project_root,
+	common::PRNumber,
+	doc_filename::DocFileName,
+	error,
+	model::PrDoc,
+	schema::Schema,
+	utils::{get_project_root, read_dir},
 };
 
 /// Wrapper around filename and content of a `prdoc` file
@@ -17,6 +22,9 @@ pub struct DocFile {
 	/// The content of the PRDoc
 	pub content: Value,
 
+	/// The typed content of the PRDoc
+	pub prdoc: PrDoc,
+
 	/// Schema
 	pub schema: Schema,
 }
@@ -30,32 +38,25 @@ pub struct DocFile {
 
 impl DocFile {
 	/// Create a new instance of a `prdoc` file
-	pub fn new(schema: Schema, file: PathBuf) -> Self {
-		let content = Self::load(schema.clone(), &file).unwrap();
-		Self { file, content, schema }
+	pub fn new(schema: Schema, file: PathBuf) -> error::Result<Self> {
+		let (content, prdoc) = Self::load(schema.clone(), &file)?;
+		Ok(Self { file, content, prdoc, schema })
 	}
 
 	/// Load a `prdoc` file given its PR number
-	pub fn load_from_number(schema: Schema, n: PRNumber) -> Self {
+	pub fn load_from_number(schema: Schema, n: PRNumber) -> error::Result<Self> {
 		let filename = DocFileName::from(n);
-		let file = PathBuf::from(filename);
-		let content = Self::load(schema.clone(), &file).unwrap();
-		Self { file, content, schema }
+		Self::new(schema, PathBuf::from(filename))
 	}
 
 	/// Attempt to load a `prdoc` file given its filename and schema
-	pub fn load(schema: Schema, file: &PathBuf) -> crate::error::Result<Value> {
+	pub fn load(schema: Schema, file: &PathBuf) -> crate::error::Result<(Value, PrDoc)> {
 		schema.load(file)
 	}
 
 	/// Generate a new PRDoc
 	pub fn generate(file: PathBuf) -> error::Result<String> {
-		let template_file = if file.is_absolute() {
-			file
-		} else {
-			let repo_root = get_project_root().expect("We need to work in a repo");
-			repo_root.join(file)
-		};
+		let template_file = if file.is_absolute() { file } else { get_project_root()?.join(file) };
 
 		match fs::read_to_string(&template_file) {
 			Ok(res) => Ok(res),
@@ -73,7 +74,7 @@ impl DocFile {
 	) -> crate::error::Result<impl Iterator<Item = PathBuf>> {
 		trace!("valid_only: {valid_only}");
 
-		let res = std::fs::read_dir(dir)?
+		let res = read_dir(dir)?
 			.filter_map(|res| res.ok())
 			// Map the directory entries to paths
 			.map(|dir_entry| dir_entry.path())
@@ -103,6 +104,12 @@ impl DocFile {
 				}
 			})
 			.filter_map(move |path| {
+				// Validating is only required to filter out invalid files. Callers interested in
+				// all the files will load them anyway.
+				if !valid_only {
+					return Some(path);
+				}
+
 				let schema_valid = schema.check_file(&path);
 				trace!(
 					"{}: schema {}",
@@ -110,14 +117,10 @@ impl DocFile {
 					if schema_valid { " VALID " } else { "INVALID" }
 				);
 
-				if valid_only {
-					if schema_valid {
-						Some(path)
-					} else {
-						None
-					}
-				} else {
+				if schema_valid {
 					Some(path)
+				} else {
+					None
 				}
 			});
 		Ok(res)
@@ -1,6 +1,6 @@
 //! A wrapper to serialize both content and filename
 
-use crate::doc_filename::DocFileName;
+use crate::{doc_filename::DocFileName, model::PrDoc};
 use serde::Serialize;
 use serde_yaml::Value;
 use std::path::PathBuf;
@@ -20,12 +20,84 @@ pub struct DocFileWrapper {
 
 	/// The content of the PRDoc
 	pub content: Option<Value>,
+
+	/// The typed content of the PRDoc. It is not serialized since it is redundant with `content`.
+	#[serde(skip)]
+	pub prdoc: Option<PrDoc>,
+
+	/// The files of a PRDoc split in several parts, empty otherwise
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub parts: Vec<PathBuf>,
 }
 
 impl DocFileWrapper {
 	/// Create a new wrapper
-	pub fn new(file: PathBuf, filename: DocFileName, content: Option<Value>) -> Self {
-		let file = file.canonicalize().expect("Canonicalize works");
-		Self { file, doc_filename: filename, content }
+	pub fn new(file: PathBuf, filename: DocFileName, content: Option<(Value, PrDoc)>) -> Self {
+		let file = file.canonicalize().unwrap_or(file);
+		let (content, prdoc) = match content {
+			Some((value, prdoc)) => (Some(value), Some(prdoc)),
+			None => (None, None),
+		};
+		Self { file, doc_filename: filename, content, prdoc, parts: vec![] }
+	}
+
+	/// Merge the `parts` of a PRDoc split in several files, ie `pr_1234_part1.prdoc` and
+	/// `pr_1234_part2.prdoc`. The lists, such as `doc` or `crates`, are concatenated in the order
+	/// of the parts. The other values, such as the `title`, are taken from the first part
+	/// defining them. The content is only available if all the parts could be loaded.
+	pub fn merge(mut parts: Vec<DocFileWrapper>) -> Self {
+		parts.sort_by_key(|part| part.doc_filename.part());
+
+		let number = parts.first().map(|part| part.doc_filename.number).unwrap_or_default();
+		let files: Vec<PathBuf> = parts.iter().map(|part| part.file.clone()).collect();
+		let content = parts.iter().try_fold(Value::Null, |mut merged, part| {
+			merge_values(&mut merged, part.content.clone()?);
+			Some(merged)
+		});
+		let prdoc = content.as_ref().and_then(|content| PrDoc::try_from(content).ok());
+
+		Self {
+			file: files.first().cloned().unwrap_or_default(),
+			doc_filename: DocFileName::new(number, None),
+			content,
+			prdoc,
+			parts: files,
+		}
+	}
+}
+
+/// Merge `value` into `merged`: sequences are concatenated and mappings merged recursively
+fn merge_values(merged: &mut Value, value: Value) {
+	match (merged, value) {
+		(Value::Mapping(merged), Value::Mapping(value)) =>
+			for (key, value) in value {
+				match merged.get_mut(&key) {
+					Some(existing) => merge_values(existing, value),
+					None => {
+						merged.insert(key, value);
+					},
+				}
+			},
+		(Value::Sequence(merged), Value::Sequence(value)) => merged.extend(value),
+		(merged @ Value::Null, value) => *merged = value,
+		_ => {},
+	}
+}
+
+#[cfg(test)]
+mod test_docfile_wrapper {
+	use super::*;
+
+	#[test]
+	fn test_merge_values() {
+		let mut merged = Value::Null;
+		let part1 = "title: Foo\ndoc: [a]\nmigrations: { db: [x], runtime: [] }\n";
+		let part2 = "title: Bar\ndoc: [b]\nmigrations: { db: [y], runtime: [z] }\ncrates: [c]\n";
+		merge_values(&mut merged, serde_yaml::from_str(part1).unwrap());
+		merge_values(&mut merged, serde_yaml::from_str(part2).unwrap());
+
+		let expected =
+			"title: Foo\ndoc: [a, b]\nmigrations: { db: [x, y], runtime: [z] }\ncrates: [c]\n";
+		assert_eq!(serde_yaml::from_str::<Value>(expected).unwrap(), merged);
 	}
 }
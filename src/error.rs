@@ -3,9 +3,9 @@
 use std::{convert::Infallible, path::PathBuf};
 
 use thiserror::Error;
-use valico::json_schema::{SchemaError, ValidationState};
+use valico::json_schema::SchemaError;
 
-use crate::common::PRNumber;
+use crate::{common::PRNumber, diagnostic::Diagnostic};
 
 /// Result type alias
 pub type Result<T> = std::result::Result<T, PRdocLibError>;
@@ -14,33 +14,54 @@ pub type Result<T> = std::result::Result<T, PRdocLibError>;
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum PRdocLibError {
-	#[error("ValidationErrors {0:?}")]
+	#[error("IO error: {0}")]
 	IO(std::io::Error),
 
-	#[error("Serde JSON error {0:?}")]
+	#[error("JSON error: {0}")]
 	SerdeJsonError(serde_json::Error),
 
-	#[error("Serde YAML error {0:?}")]
+	#[error("YAML error: {0}")]
 	SerdeYamlError(serde_yaml::Error),
 
-	#[error("ValidationErrors {0:?}")]
-	ValidationErrors(Box<ValidationState>),
+	#[error("TOML error: {0}")]
+	SerdeTomlError(toml::ser::Error),
+
+	#[error("The file does not comply with the schema ({} error(s))", .0.len())]
+	ValidationErrors(Vec<Diagnostic>),
 
 	#[error("Could not find the PRdoc for Pull Request #{0}. Did you forget to create a PRDoc?")]
 	NumberNotFound(PRNumber),
 
+	#[error("Several PRDoc files found for Pull Request #{0}: {1}. Name them `pr_{0}_part<N>.prdoc` if they are all needed.")]
+	DuplicatePRDoc(PRNumber, String),
+
+	#[error("Invalid parts for Pull Request #{0}: {1}")]
+	InvalidParts(PRNumber, String),
+
 	#[error("PRDoc file already exists: {0}")]
 	FileAlreadyExists(PathBuf),
 
 	#[error("The filename is not valid: {0}")]
 	InvalidFilename(PathBuf),
 
-	#[error("The config is not valid: {0}")]
-	InvalidConfig(PathBuf),
+	#[error("The config {0} is not valid: {1}")]
+	InvalidConfig(PathBuf, String),
 
 	#[error("No valid config found")]
 	MissingConfig,
 
+	#[error("The config file {0} does not exist")]
+	ConfigNotFound(PathBuf),
+
+	#[error("No Cargo.lock found in {0} or its parents, prdoc must run within a Rust project")]
+	ProjectRootNotFound(PathBuf),
+
+	#[error("The PRDoc folder {0} does not exist")]
+	MissingDirectory(PathBuf),
+
+	#[error("Invalid arguments: {0}")]
+	InvalidArguments(String),
+
 	#[error("Template file at {0} was not found")]
 	MissingTemplateFile(PathBuf),
 
@@ -53,11 +74,77 @@ pub enum PRdocLibError {
 	#[error("Schema error with {0}")]
 	SchemaError(SchemaError),
 
+	#[error("Unknown schema {0}, run `prdoc schema list` to see the embedded schemas")]
+	UnknownSchema(String),
+
+	#[error("Could not edit {0}: {1}")]
+	EditFailed(String, String),
+
+	#[error("Migration failed: {0}")]
+	MigrationFailed(String),
+
+	#[error("Invalid expression {0}: {1}")]
+	InvalidExpression(String, String),
+
+	#[error("Invalid placeholder pattern {0}: {1}")]
+	InvalidPattern(String, String),
+
+	#[error("The manifest is not valid: {0}")]
+	InvalidManifest(PathBuf),
+
+	#[error("Git error: {0}")]
+	Git(String),
+
 	// Unknown error
 	#[error("Unknown error")]
 	Unknown,
 }
 
+impl PRdocLibError {
+	/// Exit code of the cli for this error, following the conventions of `sysexits.h`:
+	/// - `64` (usage): the arguments are not valid
+	/// - `65` (validation): a PRDoc or another input is not valid
+	/// - `66` (not found): a PRDoc, a file or a folder does not exist
+	/// - `74` (IO): a file could not be read or written, or git failed
+	/// - `78` (config): the config, the schema or the template is missing or not valid
+	pub fn exit_code(&self) -> exitcode::ExitCode {
+		match self {
+			PRdocLibError::IO(e) if e.kind() == std::io::ErrorKind::NotFound => exitcode::NOINPUT,
+			PRdocLibError::IO(_) | PRdocLibError::FileAlreadyExists(_) | PRdocLibError::Git(_) =>
+				exitcode::IOERR,
+
+			PRdocLibError::SerdeJsonError(_) |
+			PRdocLibError::SerdeYamlError(_) |
+			PRdocLibError::SerdeTomlError(_) |
+			PRdocLibError::ValidationErrors(_) |
+			PRdocLibError::DuplicatePRDoc(_, _) |
+			PRdocLibError::InvalidParts(_, _) |
+			PRdocLibError::InvalidFilename(_) |
+			PRdocLibError::NoValidFileFound(_) |
+			PRdocLibError::SomeInvalidFiles(_) |
+			PRdocLibError::EditFailed(_, _) |
+			PRdocLibError::MigrationFailed(_) |
+			PRdocLibError::InvalidManifest(_) => exitcode::DATAERR,
+
+			PRdocLibError::NumberNotFound(_) | PRdocLibError::MissingDirectory(_) =>
+				exitcode::NOINPUT,
+
+			PRdocLibError::InvalidConfig(_, _) |
+			PRdocLibError::MissingConfig |
+			PRdocLibError::ConfigNotFound(_) |
+			PRdocLibError::ProjectRootNotFound(_) |
+			PRdocLibError::MissingTemplateFile(_) |
+			PRdocLibError::SchemaError(_) |
+			PRdocLibError::UnknownSchema(_) |
+			PRdocLibError::InvalidPattern(_, _) => exitcode::CONFIG,
+
+			PRdocLibError::InvalidArguments(_) | PRdocLibError::InvalidExpression(_, _) =>
+				exitcode::USAGE,
+			PRdocLibError::Unknown => exitcode::SOFTWARE,
+		}
+	}
+}
+
 impl From<std::io::Error> for PRdocLibError {
 	fn from(e: std::io::Error) -> Self {
 		PRdocLibError::IO(e)
@@ -76,6 +163,12 @@ impl From<serde_yaml::Error> for PRdocLibError {
 	}
 }
 
+impl From<toml::ser::Error> for PRdocLibError {
+	fn from(e: toml::ser::Error) -> Self {
+		PRdocLibError::SerdeTomlError(e)
+	}
+}
+
 impl From<SchemaError> for PRdocLibError {
 	fn from(e: SchemaError) -> Self {
 		PRdocLibError::SchemaError(e)
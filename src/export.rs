@@ -0,0 +1,134 @@
+//! Output of the load command in formats other than YAML and JSON:
+//! - NDJSON, one PRDoc per line, for tools processing the PRDoc one after the other
+//! - TOML, as an array of `prdoc` tables
+//! - CSV, with a row per audience and per crate of each PRDoc, for spreadsheets
+
+use crate::{docfile_wrapper::DocFileWrapper, error::Result, model::Audience};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+
+/// Columns of the CSV output
+const CSV_HEADER: [&str; 7] = ["number", "title", "file", "kind", "name", "semver", "text"];
+
+/// Write each PRDoc, or any other item, as JSON on its own line. Each line is flushed once written
+/// so that readers can process the PRDoc as they come.
+pub fn ndjson<T: Serialize, W: Write>(items: &[T], mut out: W) -> Result<()> {
+	for item in items {
+		serde_json::to_writer(&mut out, item)?;
+		writeln!(out)?;
+		out.flush()?;
+	}
+	Ok(())
+}
+
+/// Return the PRDoc as an array of `prdoc` tables. TOML has no null so the null values are
+/// omitted.
+pub fn toml(wrappers: &[DocFileWrapper]) -> Result<String> {
+	let mut prdocs = serde_json::to_value(wrappers)?;
+	remove_nulls(&mut prdocs);
+	Ok(toml::to_string(&serde_json::json!({ "prdoc": prdocs }))?)
+}
+
+/// Return the PRDoc as CSV, with a row per audience and per crate of each PRDoc. The `kind`
+/// column tells whether the row is about an `audience` or a `crate`. The `text` column holds the
+/// description for an audience and the note for a crate. The PRDoc that could not be loaded are
+/// skipped.
+pub fn csv(wrappers: &[DocFileWrapper]) -> String {
+	let mut rows = vec![CSV_HEADER.map(String::from).to_vec()];
+	for wrapper in wrappers {
+		let Some(prdoc) = &wrapper.prdoc else { continue };
+		let row = |kind: &str, name: &str, semver: &str, text: &str| {
+			vec![
+				wrapper.doc_filename.number.to_string(),
+				prdoc.title.clone(),
+				wrapper.file.display().to_string(),
+				kind.to_string(),
+				name.to_string(),
+				semver.to_string(),
+				text.trim().to_string(),
+			]
+		};
+
+		for entry in &prdoc.doc {
+			let audience = entry.audience.as_ref().map(Audience::as_str).unwrap_or_default();
+			rows.push(row("audience", audience, "", &entry.description));
+		}
+		for krate in &prdoc.crates {
+			let semver = krate.semver.map(|level| level.as_str()).unwrap_or_default();
+			rows.push(row("crate", &krate.name, semver, krate.note.as_deref().unwrap_or_default()));
+		}
+	}
+
+	rows.iter()
+		.map(|row| row.iter().map(|field| escape_csv(field)).collect::<Vec<_>>().join(",") + "\n")
+		.collect()
+}
+
+/// Quote the field if needed, as described in RFC 4180
+fn escape_csv(field: &str) -> String {
+	if field.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+fn remove_nulls(value: &mut Value) {
+	match value {
+		Value::Object(map) => {
+			map.retain(|_, value| !value.is_null());
+			map.values_mut().for_each(remove_nulls);
+		},
+		Value::Array(array) => {
+			array.retain(|value| !value.is_null());
+			array.iter_mut().for_each(remove_nulls);
+		},
+		_ => {},
+	}
+}
+
+#[cfg(test)]
+mod test_export {
+	use super::*;
+	use crate::{doc_filename::DocFileName, schema::Schema};
+	use std::path::PathBuf;
+
+	fn wrapper() -> DocFileWrapper {
+		let file = PathBuf::from("tests/data/all/pr_1237.prdoc");
+		let content = Schema::builtin("v1").unwrap().load(&file).unwrap();
+		DocFileWrapper::new(file, DocFileName::new(1237, None), Some(content))
+	}
+
+	#[test]
+	fn test_ndjson() {
+		let mut out = vec![];
+		ndjson(&[wrapper(), wrapper()], &mut out).unwrap();
+		let lines: Vec<Value> = String::from_utf8(out)
+			.unwrap()
+			.lines()
+			.map(|line| serde_json::from_str(line).unwrap())
+			.collect();
+		assert_eq!(2, lines.len());
+		assert_eq!(1237, lines[0]["doc_filename"]["number"]);
+	}
+
+	#[test]
+	fn test_toml() {
+		let toml = toml(&[wrapper()]).unwrap();
+		let value: toml::Value = toml::from_str(&toml).unwrap();
+		assert_eq!(Some("Foobar"), value["prdoc"][0]["content"]["title"].as_str());
+	}
+
+	#[test]
+	fn test_csv() {
+		let csv = csv(&[wrapper()]);
+		let lines: Vec<&str> = csv.lines().collect();
+		assert_eq!("number,title,file,kind,name,semver,text", lines[0]);
+		assert!(lines
+			.iter()
+			.any(|line| line.starts_with("1237,Foobar,") &&
+				line.ends_with(",crate,sp-foobar-a,major,")));
+		assert_eq!("\"a \"\"b\"\", c\"", escape_csv("a \"b\", c"));
+	}
+}
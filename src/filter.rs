@@ -0,0 +1,127 @@
+//! Selection of PRDoc based on their content, for instance to only keep the changes relevant to an
+//! audience when writing release notes.
+
+use crate::model::{Audience, PrDoc, SemverLevel};
+
+/// Criteria a PRDoc must match to be selected. All the criteria must match, including the repeated
+/// ones: filtering on 2 tags selects the PRDoc having both tags. An empty filter selects
+/// everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+	/// Audiences the PRDoc must be documented for
+	pub audiences: Vec<Audience>,
+
+	/// Crates the PRDoc must list
+	pub crates: Vec<String>,
+
+	/// Semver level of the crates. When `crates` are given, each of them must have this level,
+	/// otherwise at least one crate must.
+	pub semver: Option<SemverLevel>,
+
+	/// Tags of the PRDoc, compared ignoring the case
+	pub tags: Vec<String>,
+
+	/// Authors of the PRDoc. An author matches if it contains the value, ignoring the case, so
+	/// that `chevdor` matches `chevdor <chevdor@gmail.com>`.
+	pub authors: Vec<String>,
+
+	/// Only select the PRDoc with a database or runtime migration
+	pub has_migrations: bool,
+
+	/// Only select the PRDoc involving host functions
+	pub has_host_functions: bool,
+}
+
+impl Filter {
+	/// Return true if the filter has no criteria and thus selects everything
+	pub fn is_empty(&self) -> bool {
+		self == &Self::default()
+	}
+
+	/// Return true if `doc` matches all the criteria of the filter
+	pub fn matches(&self, doc: &PrDoc) -> bool {
+		let audiences = self
+			.audiences
+			.iter()
+			.all(|audience| doc.doc.iter().any(|entry| entry.audience.as_ref() == Some(audience)));
+
+		let level_matches =
+			|level: Option<SemverLevel>| self.semver.is_none() || level == self.semver;
+		let crates = if self.crates.is_empty() {
+			self.semver.is_none() || doc.crates.iter().any(|c| level_matches(c.semver))
+		} else {
+			self.crates
+				.iter()
+				.all(|name| doc.crates.iter().any(|c| &c.name == name && level_matches(c.semver)))
+		};
+
+		let tags = self.tags.iter().all(|tag| doc.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+
+		let authors = self.authors.iter().all(|author| {
+			let author = author.to_lowercase();
+			doc.authors.iter().any(|a| a.to_lowercase().contains(&author))
+		});
+
+		audiences &&
+			crates && tags &&
+			authors && (!self.has_migrations || !doc.migrations.is_empty()) &&
+			(!self.has_host_functions || !doc.host_functions.is_empty())
+	}
+}
+
+#[cfg(test)]
+mod test_filter {
+	use super::*;
+	use serde_yaml::Value;
+
+	fn load(file: &str) -> PrDoc {
+		let content = std::fs::read_to_string(format!("./tests/data/all/{file}")).unwrap();
+		PrDoc::try_from(&serde_yaml::from_str::<Value>(&content).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn test_matches() {
+		let doc_1225 = load("pr_1225.prdoc");
+		let doc_1237 = load("pr_1237.prdoc");
+		assert!(Filter::default().is_empty());
+		assert!(Filter::default().matches(&doc_1225));
+
+		let filter = Filter { audiences: vec![Audience::RuntimeUser], ..Default::default() };
+		assert!(filter.matches(&doc_1225));
+		assert!(!filter.matches(&doc_1237));
+
+		let filter = Filter { tags: vec!["FOO".into(), "bar".into()], ..Default::default() };
+		assert!(filter.matches(&doc_1225));
+		let filter = Filter { tags: vec!["foo".into(), "baz".into()], ..Default::default() };
+		assert!(!filter.matches(&doc_1225));
+
+		let filter = Filter { authors: vec!["Chevdor".into()], ..Default::default() };
+		assert!(filter.matches(&doc_1225));
+
+		let filter = Filter {
+			crates: vec!["sp-foobar-a".into()],
+			semver: Some(SemverLevel::Major),
+			..Default::default()
+		};
+		assert!(filter.matches(&doc_1237));
+		let filter = Filter {
+			crates: vec!["sp-foobar-b".into()],
+			semver: Some(SemverLevel::Major),
+			..Default::default()
+		};
+		assert!(!filter.matches(&doc_1237));
+		let filter = Filter { semver: Some(SemverLevel::Minor), ..Default::default() };
+		assert!(!filter.matches(&doc_1237));
+
+		let filter = Filter {
+			audiences: vec![Audience::Validator],
+			has_host_functions: true,
+			..Default::default()
+		};
+		assert!(!filter.matches(&doc_1237));
+
+		let filter = Filter { has_migrations: true, ..Default::default() };
+		assert!(filter.matches(&doc_1237));
+		assert!(!filter.matches(&load("pr_1239_no migrations.prdoc")));
+	}
+}
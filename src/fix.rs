@@ -0,0 +1,215 @@
+//! Registry of the safe fixes applied by `prdoc check --fix`.
+//!
+//! A [Fix] remediates a common and mechanical problem, such as a required array left empty. Like
+//! the migrations, the fixes edit the document through a [YamlEditor] so that the comments and
+//! the layout of the files are preserved. Fixes must never guess content: when a problem requires
+//! a decision from the author, it is left to the check to report.
+
+use crate::{doc_filename::DocFileName, error::Result, yaml_edit::YamlEditor};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// A safe fix of a common problem
+#[derive(Debug, Clone, Copy)]
+pub struct Fix {
+	/// Identifier of the fix
+	pub id: &'static str,
+
+	/// Short description of the problem fixed
+	pub description: &'static str,
+
+	/// Apply the fix to a document and return a description of each change
+	pub apply: fn(&mut YamlEditor) -> Result<Vec<String>>,
+}
+
+/// All the known fixes
+pub const FIXES: &[Fix] = &[
+	Fix {
+		id: "migrations",
+		description:
+			"Add the `migrations.db` and `migrations.runtime` arrays when they are missing",
+		apply: migrations,
+	},
+	Fix {
+		id: "host-functions",
+		description: "Add the `host_functions` array when it is missing",
+		apply: host_functions,
+	},
+	Fix {
+		id: "semver-case",
+		description: "Normalize the semver levels such as `Major` or `no_change`",
+		apply: semver_case,
+	},
+];
+
+/// The semver levels accepted by the schema
+const SEMVER_LEVELS: &[&str] = &["major", "minor", "patch", "no change"];
+
+/// Apply the `fixes` to the YAML `source`. The fixed source is returned with a description of
+/// each change. A fix that fails is skipped.
+pub fn apply(fixes: &[Fix], source: &str) -> Result<(String, Vec<String>)> {
+	let mut editor = YamlEditor::new(source)?;
+	let mut changes = vec![];
+
+	for fix in fixes {
+		match (fix.apply)(&mut editor) {
+			Ok(fix_changes) => changes.extend(fix_changes),
+			Err(e) => log::warn!("The fix {} could not be applied: {e}", fix.id),
+		}
+	}
+
+	Ok((editor.into_source(), changes))
+}
+
+/// Return the canonical name of a PRDoc file, ie `pr_1235_junk.prdoc` for `pr_1235 - junk.prdoc`,
+/// if it differs from the current one. `None` is returned when the name is already canonical or
+/// when it contains no PR number.
+pub fn canonical_filename(file: &Path) -> Option<PathBuf> {
+	let filename = DocFileName::try_from(&file.to_path_buf()).ok()?;
+	// The number is kept as written, `pr_000` is as valid as `pr_0`
+	let name = file.file_name()?.to_str()?;
+	let number: String = name.get(3..)?.chars().take_while(|c| c.is_ascii_digit()).collect();
+	let slug = filename.title.map(|title| title.slug()).unwrap_or_default();
+	let canonical = if slug.is_empty() {
+		format!("pr_{number}.prdoc")
+	} else {
+		format!("pr_{number}_{slug}.prdoc")
+	};
+
+	let canonical = file.with_file_name(canonical);
+	(canonical != file).then_some(canonical)
+}
+
+/// Return the node at `path` of the document
+fn get(editor: &YamlEditor, path: &str) -> Result<Option<Value>> {
+	let value = serde_json::to_value(editor.value()?)?;
+	Ok(value.pointer(path).cloned())
+}
+
+/// Make sure the entry `key` of the mapping at `path` is an array, adding an empty one if the
+/// entry is missing or empty
+fn empty_array(editor: &mut YamlEditor, path: &str, key: &str) -> Result<Vec<String>> {
+	let child = format!("{path}/{key}");
+	let name = child.trim_start_matches('/').replace('/', ".");
+
+	match get(editor, &child)? {
+		None => {
+			editor.insert(path, key, "[]")?;
+			Ok(vec![format!("Added the missing `{name}: []`")])
+		},
+		Some(Value::Null) => {
+			editor.set_raw(&child, "[]")?;
+			Ok(vec![format!("Set the empty `{name}` to `[]`")])
+		},
+		Some(_) => Ok(vec![]),
+	}
+}
+
+fn migrations(editor: &mut YamlEditor) -> Result<Vec<String>> {
+	match get(editor, "/migrations")? {
+		None => {
+			editor.insert("", "migrations", "{ db: [], runtime: [] }")?;
+			Ok(vec!["Added the missing `migrations` with empty `db` and `runtime`".into()])
+		},
+		Some(Value::Null) => {
+			editor.set_raw("/migrations", "{ db: [], runtime: [] }")?;
+			Ok(vec!["Set the empty `migrations` to empty `db` and `runtime`".into()])
+		},
+		Some(Value::Object(_)) => {
+			let mut changes = empty_array(editor, "/migrations", "db")?;
+			changes.extend(empty_array(editor, "/migrations", "runtime")?);
+			Ok(changes)
+		},
+		Some(_) => Ok(vec![]),
+	}
+}
+
+fn host_functions(editor: &mut YamlEditor) -> Result<Vec<String>> {
+	empty_array(editor, "", "host_functions")
+}
+
+fn semver_case(editor: &mut YamlEditor) -> Result<Vec<String>> {
+	let crates = match get(editor, "/crates")? {
+		Some(Value::Array(crates)) => crates,
+		_ => return Ok(vec![]),
+	};
+
+	let mut changes = vec![];
+	for (index, krate) in crates.iter().enumerate() {
+		let Some(semver) = krate.get("semver").and_then(|s| s.as_str()) else { continue };
+		let normalized = semver.trim().to_lowercase().replace(['_', '-'], " ");
+
+		if normalized != semver && SEMVER_LEVELS.contains(&normalized.as_str()) {
+			editor.set_scalar(&format!("/crates/{index}/semver"), &normalized)?;
+			changes.push(format!("Changed the semver `{semver}` to `{normalized}`"));
+		}
+	}
+	Ok(changes)
+}
+
+#[cfg(test)]
+mod test_fix {
+	use super::*;
+
+	#[test]
+	fn test_apply() {
+		let source = r#"# Schema: Polkadot SDK PRDoc Schema (prdoc) v1.0.0
+title: Foo
+
+doc:
+  - audience: Node Dev
+    description: Foo
+
+migrations:
+  db:
+
+crates:
+  - name: foo
+    semver: Major
+  - name: bar
+    semver: no_change
+  - name: baz
+    semver: minor
+"#;
+
+		let (fixed, changes) = apply(FIXES, source).unwrap();
+		assert_eq!(5, changes.len());
+		assert!(fixed.starts_with("# Schema: Polkadot SDK PRDoc Schema (prdoc) v1.0.0\n"));
+		assert!(fixed.contains("migrations:\n  db: []\n  runtime: []\n"));
+		assert!(fixed.ends_with("    semver: minor\nhost_functions: []\n"));
+		assert!(fixed.contains("    semver: major\n"));
+		assert!(fixed.contains("    semver: no change\n"));
+
+		let (fixed_again, changes) = apply(FIXES, &fixed).unwrap();
+		assert_eq!(fixed, fixed_again);
+		assert!(changes.is_empty());
+	}
+
+	#[test]
+	fn test_missing_migrations() {
+		let (fixed, _) = apply(FIXES, "title: Foo\nmigrations:\nhost_functions: []\n").unwrap();
+		assert_eq!("title: Foo\nmigrations: { db: [], runtime: [] }\nhost_functions: []\n", fixed);
+
+		let (fixed, _) = apply(FIXES, "title: Foo\nhost_functions: []\n").unwrap();
+		assert!(fixed.ends_with("migrations: { db: [], runtime: [] }\n"));
+	}
+
+	#[test]
+	fn test_canonical_filename() {
+		let canonical = |name: &str| canonical_filename(&PathBuf::from("prdoc").join(name));
+
+		assert_eq!(
+			Some(PathBuf::from("prdoc/pr_1235_junk.prdoc")),
+			canonical("pr_1235 - junk.prdoc")
+		);
+		assert_eq!(Some(PathBuf::from("prdoc/pr_1238.prdoc")), canonical("pr_1238_.prdoc"));
+		assert_eq!(
+			Some(PathBuf::from("prdoc/pr_1239_Whatever.prdoc")),
+			canonical("pr_1239 Whatever.prdoc")
+		);
+		assert_eq!(None, canonical("pr_1234_some_test_minimal.prdoc"));
+		assert_eq!(None, canonical("pr_1234.prdoc"));
+		assert_eq!(Some(PathBuf::from("prdoc/pr_000.prdoc")), canonical("pr_000_.prdoc"));
+		assert_eq!(None, canonical("foo.prdoc"));
+	}
+}
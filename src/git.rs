@@ -0,0 +1,176 @@
+//! Minimal read access to a local git repository. The `git` binary is used so that no extra library
+//! is required and the behavior matches what users get from their terminal.
+
+use crate::error::{PRdocLibError, Result};
+use std::{
+	io::{BufRead, BufReader, Read, Write},
+	path::{Path, PathBuf},
+	process::{Command, Stdio},
+};
+
+/// How a file changed between two revisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+	/// The file was added
+	Added,
+
+	/// The content or the type of the file changed
+	Modified,
+
+	/// The file was deleted. Renamed files are reported as deleted and added.
+	Deleted,
+}
+
+/// A local git repository
+#[derive(Debug, Clone)]
+pub struct Git {
+	repo: PathBuf,
+}
+
+impl Git {
+	/// Use the repository containing `repo`
+	pub fn new<P: AsRef<Path>>(repo: P) -> Self {
+		Self { repo: repo.as_ref().to_path_buf() }
+	}
+
+	/// Run git with the given arguments and return its output
+	fn run(&self, args: &[&str]) -> Result<String> {
+		log::trace!("git {}", args.join(" "));
+		let output = Command::new("git")
+			.arg("-C")
+			.arg(&self.repo)
+			.args(args)
+			.output()
+			.map_err(|e| PRdocLibError::Git(format!("Failed running git: {e}")))?;
+
+		if !output.status.success() {
+			let stderr = String::from_utf8_lossy(&output.stderr);
+			return Err(PRdocLibError::Git(format!("git {}: {}", args.join(" "), stderr.trim())));
+		}
+		Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+	}
+
+	/// Resolve a revision, such as a branch or a tag, to a commit hash
+	pub fn rev_parse(&self, rev: &str) -> Result<String> {
+		Ok(self
+			.run(&["rev-parse", "--verify", &format!("{rev}^{{commit}}")])?
+			.trim()
+			.to_string())
+	}
+
+	/// Return the root of the working tree
+	pub fn root(&self) -> Result<PathBuf> {
+		Ok(PathBuf::from(self.run(&["rev-parse", "--show-toplevel"])?.trim()))
+	}
+
+	/// List the files changed between `rev` and `HEAD`, or the index when `staged` is set. The
+	/// paths are relative to the root of the repository.
+	pub fn diff_files(&self, rev: &str, staged: bool) -> Result<Vec<(FileStatus, PathBuf)>> {
+		let mut args = vec!["diff", "--name-status", "--no-renames", "-z"];
+		if staged {
+			args.extend(["--cached", rev]);
+		} else {
+			args.extend([rev, "HEAD"]);
+		}
+		args.push("--");
+
+		// With `-z`, each status is followed by the path, both terminated by a NUL
+		let output = self.run(&args)?;
+		let mut fields = output.split('\0').filter(|field| !field.is_empty());
+		let mut files = vec![];
+		while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+			let status = match status.chars().next() {
+				Some('A') => FileStatus::Added,
+				Some('M' | 'T') => FileStatus::Modified,
+				Some('D') => FileStatus::Deleted,
+				_ => continue,
+			};
+			files.push((status, PathBuf::from(path)));
+		}
+		Ok(files)
+	}
+
+	/// List the files tracked at `rev`, relative to the root of the repository
+	pub fn ls_files(&self, rev: &str) -> Result<Vec<PathBuf>> {
+		Ok(self
+			.run(&["ls-tree", "-r", "--name-only", rev])?
+			.lines()
+			.map(PathBuf::from)
+			.collect())
+	}
+
+	/// Return the content of a file at `rev`. The `path` is relative to the root of the repository.
+	pub fn show(&self, rev: &str, path: &Path) -> Result<String> {
+		self.run(&["show", &format!("{rev}:{}", path.display())])
+	}
+
+	/// Return the content of several files at `rev` using a single git process. Files missing at
+	/// `rev` are returned as `None`.
+	pub fn show_all(&self, rev: &str, paths: &[PathBuf]) -> Result<Vec<Option<String>>> {
+		let error = |e: std::io::Error| PRdocLibError::Git(format!("Failed running git: {e}"));
+		let mut child = Command::new("git")
+			.arg("-C")
+			.arg(&self.repo)
+			.args(["cat-file", "--batch"])
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.spawn()
+			.map_err(error)?;
+
+		// Writing from another thread prevents a deadlock once the pipes are full
+		let mut stdin = child.stdin.take().expect("stdin is piped");
+		let input: String =
+			paths.iter().map(|path| format!("{rev}:{}\n", path.display())).collect();
+		let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+		let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+		let mut contents = Vec::with_capacity(paths.len());
+		for _ in paths {
+			let mut header = String::new();
+			stdout.read_line(&mut header).map_err(error)?;
+
+			// The header is either `<object> blob <size>` or `<name> missing`
+			let size = match header.trim_end().rsplit_once(' ') {
+				Some((_, size)) if !header.trim_end().ends_with(" missing") =>
+					size.parse::<usize>().map_err(|e| PRdocLibError::Git(e.to_string()))?,
+				_ => {
+					contents.push(None);
+					continue;
+				},
+			};
+
+			let mut content = vec![0; size + 1];
+			stdout.read_exact(&mut content).map_err(error)?;
+			content.truncate(size);
+			contents.push(Some(String::from_utf8_lossy(&content).into_owned()));
+		}
+
+		writer.join().expect("The writer does not panic").map_err(error)?;
+		child.wait().map_err(error)?;
+		Ok(contents)
+	}
+}
+
+#[cfg(test)]
+mod test_git {
+	use super::*;
+
+	#[test]
+	fn test_git() {
+		let git = Git::new(".");
+		let head = git.rev_parse("HEAD").unwrap();
+		assert_eq!(40, head.len());
+		assert!(git.ls_files(&head).unwrap().contains(&PathBuf::from("Cargo.toml")));
+		assert!(git.show(&head, Path::new("Cargo.toml")).unwrap().contains("parity-prdoc"));
+		assert!(git.rev_parse("does-not-exist").is_err());
+		assert!(git.root().unwrap().join("Cargo.toml").exists());
+		assert!(git.diff_files(&head, false).unwrap().is_empty());
+
+		let files =
+			[PathBuf::from("Cargo.toml"), PathBuf::from("missing"), PathBuf::from("LICENSE")];
+		let contents = git.show_all(&head, &files).unwrap();
+		assert!(contents[0].as_ref().unwrap().contains("parity-prdoc"));
+		assert_eq!(None, contents[1]);
+		assert!(contents[2].is_some());
+	}
+}
@@ -4,6 +4,7 @@
 pub mod commands;
 pub mod common;
 pub mod config;
+pub mod diagnostic;
 
 pub mod doc_filename;
 pub mod docfile;
@@ -11,6 +12,18 @@ pub mod docfile_wrapper;
 pub mod prdoc_source;
 
 pub mod error;
+pub mod export;
+pub mod filter;
+pub mod fix;
+pub mod git;
+pub mod lint;
+pub mod migration;
+pub mod model;
+pub mod report;
 pub mod schema;
+pub mod select;
+pub mod source_map;
 pub mod title;
 pub mod utils;
+pub mod workspace;
+pub mod yaml_edit;
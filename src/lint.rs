@@ -0,0 +1,975 @@
+//! Semantic checks of PRDoc files.
+//!
+//! The schema describes the structure of a PRDoc but cannot express rules involving several
+//! properties, such as "a crate may appear only once". Such rules are implemented as lints running
+//! on valid documents. Each [Rule] has an id and a default severity that can be changed, or the
+//! rule disabled, from the `[lints]` table of the config:
+//!
+//! ```toml
+//! [lints]
+//! duplicate-crate = "error"
+//! major-bump-note = "allow"
+//! ```
+//!
+//! Findings can also be suppressed from the PRDoc itself using comments:
+//! - `# prdoc-lint: disable=rule-a,rule-b` suppresses the findings located on the same line or, if
+//!   the comment is alone on its line, on the next line.
+//! - `# prdoc-lint: disable-file=rule-a` suppresses the findings in the whole file.
+//!
+//! `all` can be used instead of a list of rules.
+//!
+//! The `template-placeholder` rule flags the fields still holding the value of the template used
+//! by `prdoc generate` as well as the values matching one of the placeholder patterns. It needs
+//! the content of the template, see [Linter::with_template].
+//!
+//! The `filename-format` and `filename-title` rules check the name of the file rather than its
+//! content, see [Rule::check_file].
+
+use crate::{
+	diagnostic::{Diagnostic, Severity},
+	doc_filename::DocFileName,
+	error::{PRdocLibError, Result},
+	fix::canonical_filename,
+	model::{Audience, PrDoc, SemverLevel},
+	source_map::SourceMap,
+	title::Title,
+	workspace::Workspace,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{BTreeMap, HashMap},
+	path::Path,
+	sync::{Arc, LazyLock},
+};
+
+/// Prefix of the codes of the diagnostics reported by the lints
+pub const LINT_PREFIX: &str = "lint/";
+
+/// Level of a rule, as set in the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+	/// The rule is disabled
+	#[serde(alias = "off")]
+	Allow,
+
+	/// Findings are reported as warnings
+	#[serde(alias = "warn")]
+	Warning,
+
+	/// Findings are reported as errors and make the check fail
+	Error,
+}
+
+impl From<Severity> for LintLevel {
+	fn from(severity: Severity) -> Self {
+		match severity {
+			Severity::Error => LintLevel::Error,
+			Severity::Warning => LintLevel::Warning,
+		}
+	}
+}
+
+/// A problem found by a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+	/// Path of the node related to the problem, ie `/crates/1/name`. An empty path relates to the
+	/// file itself rather than to its content, such as its name.
+	pub path: String,
+
+	/// Description of the problem
+	pub message: String,
+
+	/// An optional hint helping to fix the problem
+	pub hint: Option<String>,
+}
+
+impl Finding {
+	/// Create a new finding for the node at `path`
+	pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+		Self { path: path.into(), message: message.into(), hint: None }
+	}
+
+	/// Attach a hint to the finding
+	pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+		self.hint = Some(hint.into());
+		self
+	}
+}
+
+/// A lint rule
+pub trait Rule: Send + Sync {
+	/// Id of the rule, used in the config and in the suppression comments
+	fn id(&self) -> &'static str;
+
+	/// Short description of what the rule checks
+	fn description(&self) -> &'static str;
+
+	/// Severity of the findings unless configured otherwise
+	fn default_severity(&self) -> Severity {
+		Severity::Warning
+	}
+
+	/// Level of the rule unless configured otherwise. Optional rules are disabled by default.
+	fn default_level(&self) -> LintLevel {
+		self.default_severity().into()
+	}
+
+	/// Check a document
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding>;
+
+	/// Check a document knowing the `file` it was loaded from. Rules about the file itself, such
+	/// as its name, override this method.
+	fn check_file(&self, _file: &Path, prdoc: &PrDoc) -> Vec<Finding> {
+		self.check(prdoc)
+	}
+}
+
+/// Run a set of rules on PRDoc files
+pub struct Linter {
+	rules: Vec<Box<dyn Rule>>,
+	levels: BTreeMap<String, LintLevel>,
+}
+
+impl Default for Linter {
+	fn default() -> Self {
+		Self::new(BTreeMap::new())
+	}
+}
+
+impl Linter {
+	/// Create a linter running the builtin rules at the given `levels`. Rules missing from `levels`
+	/// use their default severity.
+	pub fn new(levels: BTreeMap<String, LintLevel>) -> Self {
+		let linter = Self { rules: builtin_rules(), levels };
+		// Codes such as `schema/deprecated` refer to the other diagnostics
+		for id in linter.levels.keys().filter(|id| !id.contains('/')) {
+			if !linter.rules.iter().any(|r| r.id() == id) {
+				log::warn!("Unknown lint rule in the config: {id}");
+			}
+		}
+		linter
+	}
+
+	/// Add a rule. A rule with the same id is replaced.
+	pub fn with_rule(mut self, rule: impl Rule + 'static) -> Self {
+		self.rules.retain(|r| r.id() != rule.id());
+		self.rules.push(Box::new(rule));
+		self
+	}
+
+	/// Compare the documents with the `template` and flag the values matching the placeholder
+	/// `patterns`
+	pub fn with_template(self, template: Option<&str>, patterns: &[String]) -> Result<Self> {
+		Ok(self.with_rule(TemplatePlaceholder::new(template, patterns)?))
+	}
+
+	/// Return the rules known to the linter
+	pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+		self.rules.iter().map(|r| r.as_ref())
+	}
+
+	/// Return the level of a rule
+	pub fn level(&self, rule: &dyn Rule) -> LintLevel {
+		self.levels.get(rule.id()).copied().unwrap_or(rule.default_level())
+	}
+
+	/// Return whether the rule with the given `id` is enabled
+	pub fn is_enabled(&self, id: &str) -> bool {
+		self.rules().any(|rule| rule.id() == id && self.level(rule) != LintLevel::Allow)
+	}
+
+	/// Apply the levels configured for the warnings that are not reported by the lints, such as
+	/// `schema/deprecated`. Those warnings can be disabled or turned into errors. Errors cannot be
+	/// downgraded as they make the document unusable.
+	pub fn override_severity(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+		diagnostics
+			.into_iter()
+			.filter_map(|mut diagnostic| {
+				if diagnostic.severity == Severity::Warning {
+					match self.levels.get(&diagnostic.code) {
+						Some(LintLevel::Allow) => return None,
+						Some(LintLevel::Error) => diagnostic.severity = Severity::Error,
+						Some(LintLevel::Warning) | None => {},
+					}
+				}
+				Some(diagnostic)
+			})
+			.collect()
+	}
+
+	/// Check the crates against the `workspace`
+	pub fn with_workspace(self, workspace: Workspace) -> Self {
+		let workspace = Arc::new(workspace);
+		self.with_rule(UnknownCrate { workspace: Some(workspace.clone()) })
+			.with_rule(UnpublishedCrate { workspace: Some(workspace) })
+	}
+
+	/// Lint a valid PRDoc. The `source` is used to locate the findings and to find the suppression
+	/// comments.
+	pub fn lint(&self, file: &Path, source: &str, prdoc: &PrDoc) -> Vec<Diagnostic> {
+		let source_map = SourceMap::parse(source);
+		let suppressions = Suppressions::parse(source);
+
+		let mut diagnostics: Vec<Diagnostic> = self
+			.rules
+			.iter()
+			.flat_map(|rule| {
+				let severity = match self.level(rule.as_ref()) {
+					LintLevel::Allow => return vec![],
+					LintLevel::Warning => Severity::Warning,
+					LintLevel::Error => Severity::Error,
+				};
+
+				rule.check_file(file, prdoc)
+					.into_iter()
+					.map(|finding| {
+						let source_map = source_map.as_ref().filter(|_| !finding.path.is_empty());
+						Diagnostic::new(
+							severity,
+							format!("{LINT_PREFIX}{}", rule.id()),
+							finding.message,
+							file,
+							&finding.path,
+							source,
+							source_map,
+						)
+						.with_hint(finding.hint)
+					})
+					.filter(|d| !suppressions.is_suppressed(rule.id(), d))
+					.collect()
+			})
+			.collect();
+
+		diagnostics.sort_by_key(|d| d.range.map(|r| r.start));
+		diagnostics
+	}
+}
+
+/// The rules shipped with prdoc
+pub fn builtin_rules() -> Vec<Box<dyn Rule>> {
+	vec![
+		Box::new(DuplicateCrate),
+		Box::new(MajorBumpNote),
+		Box::new(RuntimeUserDoc),
+		Box::new(HostFunctionNotes),
+		Box::new(TemplatePlaceholder::default()),
+		Box::new(UnknownCrate::default()),
+		Box::new(UnpublishedCrate::default()),
+		Box::new(FilenameFormat),
+		Box::new(FilenameTitle),
+		Box::new(MissingAuthors),
+	]
+}
+
+/// PRDocs should mention their authors. This rule is disabled by default.
+pub struct MissingAuthors;
+
+impl Rule for MissingAuthors {
+	fn id(&self) -> &'static str {
+		"missing-authors"
+	}
+
+	fn description(&self) -> &'static str {
+		"The optional `authors` should be provided"
+	}
+
+	fn default_level(&self) -> LintLevel {
+		LintLevel::Allow
+	}
+
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding> {
+		if !prdoc.authors.is_empty() {
+			return vec![]
+		}
+		vec![Finding::new("/title", "The PRDoc does not mention its `authors`")
+			.with_hint("Add your name, GitHub handle or email to `authors`")]
+	}
+}
+
+/// Filenames should follow `pr_<number>[_<title>].prdoc` without spaces nor stray separators
+pub struct FilenameFormat;
+
+impl Rule for FilenameFormat {
+	fn id(&self) -> &'static str {
+		"filename-format"
+	}
+
+	fn description(&self) -> &'static str {
+		"Filenames should not contain spaces nor leading or trailing separators"
+	}
+
+	fn check(&self, _prdoc: &PrDoc) -> Vec<Finding> {
+		vec![]
+	}
+
+	fn check_file(&self, file: &Path, _prdoc: &PrDoc) -> Vec<Finding> {
+		let name = file.file_name().unwrap_or_default().to_string_lossy();
+		let hint = canonical_filename(file).and_then(|canonical| {
+			let canonical = canonical.file_name()?.to_string_lossy().to_string();
+			Some(format!("Rename the file to `{canonical}`, `prdoc check --fix` does it for you"))
+		});
+
+		DocFileName::format_issues(file)
+			.into_iter()
+			.map(|issue| {
+				let finding = Finding::new("", format!("Invalid filename `{name}`: {issue}"));
+				match &hint {
+					Some(hint) => finding.with_hint(hint),
+					None => finding,
+				}
+			})
+			.collect()
+	}
+}
+
+/// The title in the filename should be derived from the title of the document. This rule is
+/// disabled by default.
+pub struct FilenameTitle;
+
+impl Rule for FilenameTitle {
+	fn id(&self) -> &'static str {
+		"filename-title"
+	}
+
+	fn description(&self) -> &'static str {
+		"The title in the filename should be derived from the title of the document"
+	}
+
+	fn default_level(&self) -> LintLevel {
+		LintLevel::Allow
+	}
+
+	fn check(&self, _prdoc: &PrDoc) -> Vec<Finding> {
+		vec![]
+	}
+
+	fn check_file(&self, file: &Path, prdoc: &PrDoc) -> Vec<Finding> {
+		let Ok(filename) = DocFileName::try_from(&file.to_path_buf()) else { return vec![] };
+		let Some(title) = &filename.title else { return vec![] };
+
+		// The parts of a split PRDoc may not share the title of the document
+		let slug = title.slug();
+		let (part, slug) = match filename.part() {
+			Some(_) =>
+				slug.split_once('_').map(|(part, slug)| (Some(part), slug)).unwrap_or_default(),
+			None => (None, slug.as_str()),
+		};
+		let expected = Title::from(prdoc.title.as_str()).slug();
+		if slug.is_empty() || slug.eq_ignore_ascii_case(&expected) {
+			return vec![]
+		}
+
+		vec![Finding::new(
+			"/title",
+			format!("The filename title `{slug}` does not match the title of the document"),
+		)
+		.with_hint(format!(
+			"Expected `pr_{}{}_{expected}.prdoc`",
+			filename.number,
+			part.map(|part| format!("_{part}")).unwrap_or_default()
+		))]
+	}
+}
+
+/// Rules suppressed by comments
+#[derive(Debug, Default)]
+struct Suppressions {
+	file: Vec<String>,
+	/// Suppressed rules per line, starting at 1
+	lines: HashMap<usize, Vec<String>>,
+}
+
+impl Suppressions {
+	fn parse(source: &str) -> Self {
+		static SUPPRESSION: LazyLock<Regex> = LazyLock::new(|| {
+			Regex::new(r"#\s*prdoc-lint:\s*(disable|disable-file)\s*=\s*([\w,\s-]+)$").unwrap()
+		});
+		let mut suppressions = Self::default();
+
+		for (index, line) in source.lines().enumerate() {
+			let Some(captures) = SUPPRESSION.captures(line.trim_end()) else { continue };
+			let rules = captures[2].split(',').map(|r| r.trim().to_string());
+
+			if &captures[1] == "disable-file" {
+				suppressions.file.extend(rules);
+			} else {
+				// A comment alone on its line applies to the next line
+				let standalone = line.trim_start().starts_with('#');
+				let target = if standalone { index + 2 } else { index + 1 };
+				suppressions.lines.entry(target).or_default().extend(rules);
+			}
+		}
+		suppressions
+	}
+
+	fn is_suppressed(&self, rule: &str, diagnostic: &Diagnostic) -> bool {
+		let matches = |rules: &Vec<String>| rules.iter().any(|r| r == rule || r == "all");
+		let line = diagnostic.range.map(|r| r.start.line);
+		matches(&self.file) || line.and_then(|l| self.lines.get(&l)).is_some_and(matches)
+	}
+}
+
+/// A crate may appear only once in `crates`
+pub struct DuplicateCrate;
+
+impl Rule for DuplicateCrate {
+	fn id(&self) -> &'static str {
+		"duplicate-crate"
+	}
+
+	fn description(&self) -> &'static str {
+		"A crate may appear only once in `crates`"
+	}
+
+	fn default_severity(&self) -> Severity {
+		Severity::Error
+	}
+
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding> {
+		let mut first: HashMap<&str, usize> = HashMap::new();
+		prdoc
+			.crates
+			.iter()
+			.enumerate()
+			// A missing name is not a duplicate
+			.filter(|(_, krate)| !krate.name.is_empty())
+			.filter_map(|(index, krate)| match first.get(krate.name.as_str()) {
+				Some(previous) => Some(
+					Finding::new(
+						format!("/crates/{index}/name"),
+						format!("The crate `{}` is listed more than once", krate.name),
+					)
+					.with_hint(format!("Merge it with the entry at /crates/{previous}")),
+				),
+				None => {
+					first.insert(&krate.name, index);
+					None
+				},
+			})
+			.collect()
+	}
+}
+
+/// A `major` bump needs a `note` explaining the breaking change
+pub struct MajorBumpNote;
+
+impl Rule for MajorBumpNote {
+	fn id(&self) -> &'static str {
+		"major-bump-note"
+	}
+
+	fn description(&self) -> &'static str {
+		"A `major` bump needs a `note` explaining the breaking change"
+	}
+
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding> {
+		prdoc
+			.crates
+			.iter()
+			.enumerate()
+			.filter(|(_, krate)| krate.semver == Some(SemverLevel::Major))
+			.filter(|(_, krate)| is_blank(krate.note.as_deref()))
+			.map(|(index, krate)| {
+				Finding::new(
+					format!("/crates/{index}/semver"),
+					format!("The major bump of `{}` has no `note`", krate.name),
+				)
+				.with_hint("Explain the breaking change and how to deal with it in a `note`")
+			})
+			.collect()
+	}
+}
+
+/// The documentation for the `Runtime User` audience must not be empty
+pub struct RuntimeUserDoc;
+
+impl Rule for RuntimeUserDoc {
+	fn id(&self) -> &'static str {
+		"runtime-user-doc"
+	}
+
+	fn description(&self) -> &'static str {
+		"The documentation for the `Runtime User` audience must not be empty"
+	}
+
+	fn default_severity(&self) -> Severity {
+		Severity::Error
+	}
+
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding> {
+		prdoc
+			.doc
+			.iter()
+			.enumerate()
+			.filter(|(_, entry)| entry.audience == Some(Audience::RuntimeUser))
+			.filter(|(_, entry)| is_blank(Some(&entry.description)))
+			.map(|(index, _)| {
+				Finding::new(
+					format!("/doc/{index}/description"),
+					"The description for the `Runtime User` audience is empty",
+				)
+			})
+			.collect()
+	}
+}
+
+/// Enabled host functions need `notes`
+pub struct HostFunctionNotes;
+
+impl Rule for HostFunctionNotes {
+	fn id(&self) -> &'static str {
+		"host-function-notes"
+	}
+
+	fn description(&self) -> &'static str {
+		"Enabled host functions need `notes`"
+	}
+
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding> {
+		prdoc
+			.host_functions
+			.iter()
+			.enumerate()
+			.filter(|(_, function)| function.enabled && is_blank(function.notes.as_deref()))
+			.map(|(index, function)| {
+				Finding::new(
+					format!("/host_functions/{index}"),
+					format!("The host function `{}` is enabled but has no `notes`", function.name),
+				)
+			})
+			.collect()
+	}
+}
+
+/// Crates must be members of the Cargo workspace. This rule is disabled by default.
+#[derive(Default)]
+pub struct UnknownCrate {
+	workspace: Option<Arc<Workspace>>,
+}
+
+impl Rule for UnknownCrate {
+	fn id(&self) -> &'static str {
+		"unknown-crate"
+	}
+
+	fn description(&self) -> &'static str {
+		"Crates must be members of the Cargo workspace"
+	}
+
+	fn default_severity(&self) -> Severity {
+		Severity::Error
+	}
+
+	fn default_level(&self) -> LintLevel {
+		LintLevel::Allow
+	}
+
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding> {
+		let Some(workspace) = &self.workspace else { return vec![] };
+		prdoc
+			.crates
+			.iter()
+			.enumerate()
+			// A missing name is left to the schema
+			.filter(|(_, krate)| !krate.name.is_empty() && workspace.get(&krate.name).is_none())
+			.map(|(index, krate)| {
+				let finding = Finding::new(
+					format!("/crates/{index}/name"),
+					format!("The crate `{}` is not a member of the workspace", krate.name),
+				);
+				match workspace.suggest(&krate.name).as_slice() {
+					[] => finding,
+					suggestions => finding.with_hint(format!(
+						"Did you mean {}?",
+						suggestions
+							.iter()
+							.map(|s| format!("`{s}`"))
+							.collect::<Vec<_>>()
+							.join(" or ")
+					)),
+				}
+			})
+			.collect()
+	}
+}
+
+/// Crates marked `publish = false` should not be listed. This rule is disabled by default.
+#[derive(Default)]
+pub struct UnpublishedCrate {
+	workspace: Option<Arc<Workspace>>,
+}
+
+impl Rule for UnpublishedCrate {
+	fn id(&self) -> &'static str {
+		"unpublished-crate"
+	}
+
+	fn description(&self) -> &'static str {
+		"Crates marked `publish = false` should not be listed"
+	}
+
+	fn default_level(&self) -> LintLevel {
+		LintLevel::Allow
+	}
+
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding> {
+		let Some(workspace) = &self.workspace else { return vec![] };
+		prdoc
+			.crates
+			.iter()
+			.enumerate()
+			.filter(|(_, krate)| workspace.get(&krate.name).is_some_and(|c| !c.publish))
+			.map(|(index, krate)| {
+				Finding::new(
+					format!("/crates/{index}/name"),
+					format!("The crate `{}` is marked `publish = false`", krate.name),
+				)
+				.with_hint("Unpublished crates do not need semver information")
+			})
+			.collect()
+	}
+}
+
+/// Fields must not keep the placeholder values of the template
+pub struct TemplatePlaceholder {
+	/// The free text values of the template, by path
+	template: HashMap<String, String>,
+	patterns: Vec<Regex>,
+}
+
+/// Patterns of the values considered as placeholders unless configured otherwise
+pub const DEFAULT_PLACEHOLDERS: &[&str] = &[r"^\.\.\.$", r"(?i)^(todo|tbd)$"];
+
+/// Properties containing free text, the other ones are expected to hold the same values as the
+/// template, such as the audience.
+const FREE_TEXT: &[&str] = &["title", "description", "note", "notes"];
+
+impl Default for TemplatePlaceholder {
+	fn default() -> Self {
+		let patterns = DEFAULT_PLACEHOLDERS.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+		Self::new(None, &patterns).expect("The default patterns are valid")
+	}
+}
+
+impl TemplatePlaceholder {
+	/// Create the rule from the source of the `template` and a list of placeholder `patterns`
+	pub fn new(template: Option<&str>, patterns: &[String]) -> Result<Self> {
+		let template = match template {
+			Some(source) => {
+				let value: serde_yaml::Value = serde_yaml::from_str(source)?;
+				free_text(&value)
+					.into_iter()
+					.map(|(path, text)| (path, text.trim().into()))
+					.collect()
+			},
+			None => HashMap::new(),
+		};
+
+		let patterns = patterns
+			.iter()
+			.map(|p| {
+				Regex::new(p).map_err(|e| PRdocLibError::InvalidPattern(p.clone(), e.to_string()))
+			})
+			.collect::<Result<_>>()?;
+
+		Ok(Self { template, patterns })
+	}
+}
+
+impl Rule for TemplatePlaceholder {
+	fn id(&self) -> &'static str {
+		"template-placeholder"
+	}
+
+	fn description(&self) -> &'static str {
+		"Fields must not keep the placeholder values of the template"
+	}
+
+	fn default_severity(&self) -> Severity {
+		Severity::Error
+	}
+
+	fn check(&self, prdoc: &PrDoc) -> Vec<Finding> {
+		let value = serde_yaml::Value::from(prdoc);
+		free_text(&value)
+			.into_iter()
+			.filter_map(|(path, text)| {
+				let text = text.trim();
+				let message = if self.template.get(&path).is_some_and(|t| t == text) {
+					format!("`{path}` still holds the value of the template")
+				} else if let Some(pattern) = self.patterns.iter().find(|p| p.is_match(text)) {
+					format!("`{path}` holds a placeholder matching `{pattern}`")
+				} else {
+					return None;
+				};
+				Some(
+					Finding::new(path, message)
+						.with_hint("Replace the placeholder with actual content"),
+				)
+			})
+			.collect()
+	}
+}
+
+/// Collect the free text strings of a document along with their path
+fn free_text(value: &serde_yaml::Value) -> Vec<(String, &str)> {
+	fn walk<'a>(
+		value: &'a serde_yaml::Value,
+		path: String,
+		property: Option<&str>,
+		out: &mut Vec<(String, &'a str)>,
+	) {
+		match value {
+			serde_yaml::Value::String(text) if property.is_some_and(|p| FREE_TEXT.contains(&p)) =>
+				out.push((path, text)),
+			serde_yaml::Value::Sequence(items) =>
+				for (index, item) in items.iter().enumerate() {
+					walk(item, format!("{path}/{index}"), property, out);
+				},
+			serde_yaml::Value::Mapping(map) =>
+				for (key, item) in map {
+					if let Some(key) = key.as_str() {
+						walk(item, format!("{path}/{key}"), Some(key), out);
+					}
+				},
+			_ => {},
+		}
+	}
+
+	let mut out = vec![];
+	walk(value, String::new(), None, &mut out);
+	out
+}
+
+fn is_blank(text: Option<&str>) -> bool {
+	text.unwrap_or_default().trim().is_empty()
+}
+
+#[cfg(test)]
+mod test_lint {
+	use super::*;
+
+	const SOURCE: &str = r#"title: Foobar
+
+doc:
+  - audience: Runtime User
+    description: ""
+
+crates:
+  - name: foo
+    semver: major
+  - name: bar
+    semver: patch
+  - name: foo # prdoc-lint: disable=major-bump-note
+    semver: major
+
+host_functions:
+  - name: ext_foo
+    enabled: true
+    description: Foo
+"#;
+
+	fn lint(linter: &Linter, source: &str) -> Vec<(Severity, String, usize)> {
+		let value: serde_yaml::Value = serde_yaml::from_str(source).unwrap();
+		let prdoc = PrDoc::try_from(&value).unwrap();
+		linter
+			.lint(Path::new("pr_1.prdoc"), source, &prdoc)
+			.into_iter()
+			.map(|d| (d.severity, d.code, d.range.unwrap().start.line))
+			.collect()
+	}
+
+	#[test]
+	fn test_builtin_rules() {
+		assert_eq!(
+			vec![
+				(Severity::Error, "lint/runtime-user-doc".to_string(), 5),
+				(Severity::Warning, "lint/major-bump-note".to_string(), 9),
+				(Severity::Error, "lint/duplicate-crate".to_string(), 12),
+				(Severity::Warning, "lint/major-bump-note".to_string(), 13),
+				(Severity::Warning, "lint/host-function-notes".to_string(), 16),
+			],
+			lint(&Linter::default(), SOURCE)
+		);
+	}
+
+	#[test]
+	fn test_levels() {
+		let levels = BTreeMap::from([
+			("major-bump-note".to_string(), LintLevel::Allow),
+			("host-function-notes".to_string(), LintLevel::Error),
+			("duplicate-crate".to_string(), LintLevel::Warning),
+		]);
+		assert_eq!(
+			vec![
+				(Severity::Error, "lint/runtime-user-doc".to_string(), 5),
+				(Severity::Warning, "lint/duplicate-crate".to_string(), 12),
+				(Severity::Error, "lint/host-function-notes".to_string(), 16),
+			],
+			lint(&Linter::new(levels), SOURCE)
+		);
+	}
+
+	#[test]
+	fn test_suppressions() {
+		let source = SOURCE
+			.replace("  - name: foo # prdoc-lint: disable=major-bump-note\n    semver: major", "  - name: foo # prdoc-lint: disable=duplicate-crate\n    # prdoc-lint: disable=all\n    semver: major")
+			.replace("title: Foobar", "# prdoc-lint: disable-file=host-function-notes, runtime-user-doc\ntitle: Foobar");
+
+		assert_eq!(
+			vec![(Severity::Warning, "lint/major-bump-note".to_string(), 10)],
+			lint(&Linter::default(), &source)
+		);
+	}
+
+	#[test]
+	fn test_template_placeholder() {
+		let template = std::fs::read_to_string("./template.prdoc").unwrap();
+		let linter =
+			Linter::default().with_template(Some(&template), &["(?i)fixme".into()]).unwrap();
+
+		let source = "title: \"...\"\n\ndoc:\n  - audience: Node Dev\n    description: |\n      ...\n  - audience: Validator\n    description: FIXME\n\ncrates: []\n";
+		let findings: Vec<_> = lint(&linter, source)
+			.into_iter()
+			.filter(|(_, code, _)| code == "lint/template-placeholder")
+			.collect();
+		assert_eq!(
+			vec![
+				(Severity::Error, "lint/template-placeholder".to_string(), 1),
+				(Severity::Error, "lint/template-placeholder".to_string(), 6),
+				(Severity::Error, "lint/template-placeholder".to_string(), 8),
+			],
+			findings
+		);
+
+		// Without template, the default patterns still catch the placeholders
+		assert_eq!(2, lint(&Linter::default(), source).len());
+		assert!(Linter::default().with_template(None, &["(".into()]).is_err());
+	}
+
+	#[test]
+	fn test_workspace() {
+		let source =
+			"title: Foo\ncrates:\n  - name: parity-prdc\n  - name: parity-prdoc\n  - semver: patch\n";
+		let workspace = Workspace::load(Path::new(".")).unwrap();
+
+		// The workspace rules are disabled by default
+		assert!(lint(&Linter::default().with_workspace(workspace.clone()), source).is_empty());
+
+		let levels = BTreeMap::from([("unknown-crate".to_string(), LintLevel::Error)]);
+		let linter = Linter::new(levels).with_workspace(workspace);
+		let diagnostics = linter.lint(
+			Path::new("pr_1.prdoc"),
+			source,
+			&PrDoc::try_from(&serde_yaml::from_str::<serde_yaml::Value>(source).unwrap()).unwrap(),
+		);
+		assert_eq!(1, diagnostics.len());
+		assert_eq!("/crates/0/name", diagnostics[0].path);
+		assert_eq!(Some("Did you mean `parity-prdoc`?".to_string()), diagnostics[0].hint);
+	}
+
+	#[test]
+	fn test_filename_rules() {
+		let source = "title: Add the Foo pallet\ncrates: []\n";
+		let prdoc =
+			PrDoc::try_from(&serde_yaml::from_str::<serde_yaml::Value>(source).unwrap()).unwrap();
+		let levels = BTreeMap::from([("filename-title".to_string(), LintLevel::Warning)]);
+		let linter = Linter::new(levels);
+		let codes = |name: &str| {
+			linter
+				.lint(Path::new(name), source, &prdoc)
+				.into_iter()
+				.map(|d| d.code)
+				.collect::<Vec<_>>()
+		};
+
+		assert!(codes("pr_1.prdoc").is_empty());
+		assert!(codes("pr_1_add_the_foo_pallet.prdoc").is_empty());
+		assert!(codes("pr_1_part2.prdoc").is_empty());
+		assert!(codes("pr_1_part2_Add_the_Foo_pallet.prdoc").is_empty());
+		assert_eq!(vec!["lint/filename-title"], codes("pr_1_part2_bar.prdoc"));
+
+		let diagnostics = linter.lint(Path::new("pr_1_part2_bar.prdoc"), source, &prdoc);
+		assert_eq!(
+			Some("Expected `pr_1_part2_Add_the_Foo_pallet.prdoc`".to_string()),
+			diagnostics[0].hint
+		);
+
+		let diagnostics = linter.lint(Path::new("pr_1_foo.prdoc"), source, &prdoc);
+		assert_eq!(1, diagnostics.len());
+		assert_eq!(1, diagnostics[0].range.unwrap().start.line);
+		assert_eq!(
+			Some("Expected `pr_1_Add_the_Foo_pallet.prdoc`".to_string()),
+			diagnostics[0].hint
+		);
+
+		let diagnostics = linter.lint(Path::new("pr_1 - foo.prdoc"), source, &prdoc);
+		assert_eq!(
+			vec!["lint/filename-format", "lint/filename-format", "lint/filename-title"],
+			diagnostics.iter().map(|d| d.code.as_str()).collect::<Vec<_>>()
+		);
+		assert_eq!(None, diagnostics[0].range);
+		assert_eq!(
+			Some(
+				"Rename the file to `pr_1_foo.prdoc`, `prdoc check --fix` does it for you"
+					.to_string()
+			),
+			diagnostics[0].hint
+		);
+	}
+
+	#[test]
+	fn test_severity_overrides() {
+		let source = "title: Foo\n";
+		let deprecated = || {
+			Diagnostic::new(
+				Severity::Warning,
+				"schema/deprecated",
+				"`foo` is deprecated",
+				Path::new("pr_1.prdoc"),
+				"/title",
+				source,
+				None,
+			)
+		};
+		let error =
+			Diagnostic { severity: Severity::Error, code: "schema/type".into(), ..deprecated() };
+		let linter = |level| {
+			Linter::new(BTreeMap::from([
+				("schema/deprecated".to_string(), level),
+				("schema/type".to_string(), LintLevel::Allow),
+				("missing-authors".to_string(), LintLevel::Warning),
+			]))
+		};
+
+		let diagnostics =
+			linter(LintLevel::Error).override_severity(vec![deprecated(), error.clone()]);
+		assert_eq!(
+			vec![Severity::Error, Severity::Error],
+			diagnostics.iter().map(|d| d.severity).collect::<Vec<_>>()
+		);
+		let diagnostics = linter(LintLevel::Allow).override_severity(vec![deprecated(), error]);
+		assert_eq!(
+			vec!["schema/type"],
+			diagnostics.iter().map(|d| d.code.as_str()).collect::<Vec<_>>()
+		);
+
+		assert_eq!(
+			vec![(Severity::Warning, "lint/missing-authors".to_string(), 1)],
+			lint(&linter(LintLevel::Warning), source)
+		);
+		assert!(lint(&linter(LintLevel::Warning), "title: Foo\nauthors: [foo]\n").is_empty());
+	}
+
+	#[test]
+	fn test_level_from_config() {
+		let levels: BTreeMap<String, LintLevel> =
+			toml::from_str("a = \"allow\"\nb = \"off\"\nc = \"warn\"\nd = \"error\"").unwrap();
+		assert_eq!(
+			vec![LintLevel::Allow, LintLevel::Allow, LintLevel::Warning, LintLevel::Error],
+			levels.into_values().collect::<Vec<_>>()
+		);
+	}
+}
@@ -0,0 +1,74 @@
+//! Registry of the transformations upgrading PRDoc files from one major version of the schema to
+//! the next one.
+//!
+//! Each [Migration] edits the document through a [YamlEditor] so that the comments, anchors and
+//! layout of the files are preserved as much as possible. When the schema gets a new major
+//! version, a migration from the previous version must be added to [MIGRATIONS].
+
+use crate::{
+	error::{PRdocLibError, Result},
+	yaml_edit::YamlEditor,
+};
+
+/// A transformation upgrading a document from the major version `from` of the schema to `to`
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+	/// Major version of the schema the migration applies to
+	pub from: u64,
+
+	/// Major version of the schema produced by the migration
+	pub to: u64,
+
+	/// Short description of the changes
+	pub description: &'static str,
+
+	/// Apply the migration to a document
+	pub apply: fn(&mut YamlEditor) -> Result<()>,
+}
+
+/// All the known migrations. The schema only has a single major version so far.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// Find the chain of `migrations` leading from the major version `from` to `to`
+pub fn path(migrations: &[Migration], from: u64, to: u64) -> Result<Vec<Migration>> {
+	let mut steps = vec![];
+	let mut current = from;
+
+	while current != to {
+		let step = migrations
+			.iter()
+			.filter(|m| m.from == current && m.to > current && m.to <= to)
+			.max_by_key(|m| m.to)
+			.ok_or_else(|| {
+				PRdocLibError::MigrationFailed(format!("no migration from v{current} to v{to}"))
+			})?;
+		steps.push(*step);
+		current = step.to;
+	}
+
+	Ok(steps)
+}
+
+#[cfg(test)]
+mod test_migration {
+	use super::*;
+
+	fn noop(_editor: &mut YamlEditor) -> Result<()> {
+		Ok(())
+	}
+
+	fn migration(from: u64, to: u64) -> Migration {
+		Migration { from, to, description: "noop", apply: noop }
+	}
+
+	#[test]
+	fn test_path() {
+		let migrations = [migration(1, 2), migration(2, 3), migration(3, 4), migration(2, 4)];
+
+		let steps = path(&migrations, 1, 4).unwrap();
+		assert_eq!(vec![(1, 2), (2, 4)], steps.iter().map(|m| (m.from, m.to)).collect::<Vec<_>>());
+		assert!(path(&migrations, 1, 1).unwrap().is_empty());
+		assert!(path(&migrations, 1, 5).is_err());
+		assert!(path(&migrations, 4, 1).is_err());
+	}
+}
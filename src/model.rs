@@ -0,0 +1,386 @@
+//! Typed representation of the content of a PRDoc file.
+//!
+//! The schema remains the source of truth regarding what a valid PRDoc is. The types below mirror
+//! the properties of the official schema so that tools built on top of [prdoclib](crate) do not
+//! need to dig through untyped YAML. Properties unknown to this model are not dropped: they are
+//! kept in the `extra` field of the closest struct so that custom schemas keep working.
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use crate::error::{self, PRdocLibError};
+
+/// Properties that are not part of the model but were found in the document
+pub type Extra = BTreeMap<String, Value>;
+
+/// The content of a PRDoc file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PrDoc {
+	/// Title of the change
+	#[serde(default)]
+	pub title: String,
+
+	/// Optional list of authors
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub authors: Vec<String>,
+
+	/// Optional list of tags
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
+
+	/// Description of the change for each relevant audience
+	#[serde(default)]
+	pub doc: Vec<DocEntry>,
+
+	/// Semver information about the crates impacted by the change
+	#[serde(default)]
+	pub crates: Vec<CrateChange>,
+
+	/// Database and runtime migrations
+	#[serde(default)]
+	pub migrations: Migrations,
+
+	/// Host functions involved in the change
+	#[serde(default)]
+	pub host_functions: Vec<HostFunction>,
+
+	/// Properties unknown to the model
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+/// The description of a change for a given audience
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DocEntry {
+	/// The audience this entry is written for. The schema does not require it.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub audience: Option<Audience>,
+
+	/// Optional title override for the current audience
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub title: Option<String>,
+
+	/// Description of the change
+	#[serde(default)]
+	pub description: String,
+
+	/// Optional notes
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub notes: Vec<String>,
+
+	/// Properties unknown to the model
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+/// Audiences known by the official schema. Custom schemas may define other audiences, those end
+/// up as `Audience::Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Audience {
+	/// Someone working on the node
+	NodeDev,
+
+	/// Someone working on runtimes, frames, pallets
+	RuntimeDev,
+
+	/// Someone running a node in general, not specific to validators
+	NodeOperator,
+
+	/// Someone running a Validator node
+	Validator,
+
+	/// Someone building a parachain
+	ParachainBuilder,
+
+	/// Someone building Apps and dApps
+	AppBuilder,
+
+	/// Someone involved or interested in the governance process or noticeable changes to the
+	/// runtime and/or its dependencies
+	RuntimeUser,
+
+	/// Any audience unknown to the official schema
+	Other(String),
+}
+
+impl Audience {
+	/// Return the audience as written in a PRDoc file
+	pub fn as_str(&self) -> &str {
+		match self {
+			Audience::NodeDev => "Node Dev",
+			Audience::RuntimeDev => "Runtime Dev",
+			Audience::NodeOperator => "Node Operator",
+			Audience::Validator => "Validator",
+			Audience::ParachainBuilder => "Parachain Builder",
+			Audience::AppBuilder => "App Builder",
+			Audience::RuntimeUser => "Runtime User",
+			Audience::Other(s) => s.as_str(),
+		}
+	}
+}
+
+impl From<&str> for Audience {
+	fn from(s: &str) -> Self {
+		match s {
+			"Node Dev" => Audience::NodeDev,
+			"Runtime Dev" => Audience::RuntimeDev,
+			"Node Operator" => Audience::NodeOperator,
+			"Validator" => Audience::Validator,
+			"Parachain Builder" => Audience::ParachainBuilder,
+			"App Builder" => Audience::AppBuilder,
+			"Runtime User" => Audience::RuntimeUser,
+			other => Audience::Other(other.to_string()),
+		}
+	}
+}
+
+impl From<String> for Audience {
+	fn from(s: String) -> Self {
+		Self::from(s.as_str())
+	}
+}
+
+impl From<Audience> for String {
+	fn from(audience: Audience) -> Self {
+		audience.as_str().to_string()
+	}
+}
+
+impl Display for Audience {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// Semver level of a change. The ordering goes from the weakest (`no change`) to the strongest
+/// (`major`) level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverLevel {
+	/// The change has no semver impact
+	#[serde(rename = "no change")]
+	NoChange,
+
+	/// Patch level change
+	Patch,
+
+	/// Minor level change
+	Minor,
+
+	/// Major level change
+	Major,
+}
+
+impl SemverLevel {
+	/// Return the level as written in a PRDoc file
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			SemverLevel::NoChange => "no change",
+			SemverLevel::Patch => "patch",
+			SemverLevel::Minor => "minor",
+			SemverLevel::Major => "major",
+		}
+	}
+}
+
+impl Display for SemverLevel {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl FromStr for SemverLevel {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"no change" => Ok(SemverLevel::NoChange),
+			"patch" => Ok(SemverLevel::Patch),
+			"minor" => Ok(SemverLevel::Minor),
+			"major" => Ok(SemverLevel::Major),
+			other => Err(format!("Invalid semver level: {other}")),
+		}
+	}
+}
+
+/// Semver information about a crate
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CrateChange {
+	/// Name of the crate, empty if missing as the schema does not require it
+	#[serde(default)]
+	pub name: String,
+
+	/// Semver level of the change, if provided
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub semver: Option<SemverLevel>,
+
+	/// Optional note
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub note: Option<String>,
+
+	/// Properties unknown to the model
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+/// Migrations introduced by a change
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Migrations {
+	/// Database migrations
+	#[serde(default)]
+	pub db: Vec<DbMigration>,
+
+	/// Runtime migrations
+	#[serde(default)]
+	pub runtime: Vec<RuntimeMigration>,
+
+	/// Properties unknown to the model
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+impl Migrations {
+	/// Return true if there is no migration at all
+	pub fn is_empty(&self) -> bool {
+		self.db.is_empty() && self.runtime.is_empty()
+	}
+}
+
+/// A database migration
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DbMigration {
+	/// Name of the migration
+	pub name: String,
+
+	/// Description of the migration
+	pub description: String,
+
+	/// Properties unknown to the model
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+/// A runtime migration
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RuntimeMigration {
+	/// Pallet affected by the migration
+	pub pallet: String,
+
+	/// Description of the migration
+	pub description: String,
+
+	/// Properties unknown to the model
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+/// A host function involved in a change
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HostFunction {
+	/// Name of the host function
+	pub name: String,
+
+	/// Whether the host function is enabled
+	pub enabled: bool,
+
+	/// Description of the host function
+	pub description: String,
+
+	/// Optional notes
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub notes: Option<String>,
+
+	/// Properties unknown to the model
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+impl TryFrom<&Value> for PrDoc {
+	type Error = PRdocLibError;
+
+	fn try_from(value: &Value) -> error::Result<Self> {
+		Ok(serde_yaml::from_value(value.clone())?)
+	}
+}
+
+impl From<&PrDoc> for Value {
+	fn from(doc: &PrDoc) -> Self {
+		serde_yaml::to_value(doc).expect("A PrDoc can always be serialized")
+	}
+}
+
+#[cfg(test)]
+mod test_model {
+	use super::*;
+
+	#[test]
+	fn test_semver_ordering() {
+		assert!(SemverLevel::Major > SemverLevel::Minor);
+		assert!(SemverLevel::Minor > SemverLevel::Patch);
+		assert!(SemverLevel::Patch > SemverLevel::NoChange);
+	}
+
+	#[test]
+	fn test_load_1235() {
+		let content = std::fs::read_to_string("./tests/data/some/pr_1235 - junk.prdoc").unwrap();
+		let value: Value = serde_yaml::from_str(&content).unwrap();
+		let doc = PrDoc::try_from(&value).unwrap();
+
+		assert_eq!("Foobarization of all bar", doc.title);
+		assert_eq!(3, doc.doc.len());
+		assert_eq!(Some(Audience::NodeOperator), doc.doc[2].audience);
+		assert_eq!(Some(SemverLevel::NoChange), doc.crates[2].semver);
+		assert_eq!(Some("The change was small, thus patch".into()), doc.crates[1].note);
+		assert_eq!(2, doc.migrations.runtime.len());
+		assert!(doc.host_functions.is_empty());
+		assert!(doc.extra.is_empty());
+	}
+
+	#[test]
+	fn test_extra_fields_are_kept() {
+		let value: Value = serde_yaml::from_str(
+			r#"
+title: Foo
+custom: 42
+doc:
+  - audience: Custom Audience
+    description: bar
+    priority: high
+crates: []
+"#,
+		)
+		.unwrap();
+		let doc = PrDoc::try_from(&value).unwrap();
+
+		assert_eq!(Some(&Value::from(42)), doc.extra.get("custom"));
+		assert_eq!(Some(Audience::Other("Custom Audience".into())), doc.doc[0].audience);
+		assert_eq!(Some(&Value::from("high")), doc.doc[0].extra.get("priority"));
+
+		let back = Value::from(&doc);
+		assert_eq!(Some(&Value::from(42)), back.get("custom"));
+	}
+
+	#[test]
+	fn test_optional_fields() {
+		// The schema requires neither the audience of a doc entry nor the name of a crate
+		let value: Value = serde_yaml::from_str(
+			r#"
+title: Foo
+doc:
+  - description: bar
+crates:
+  - semver: patch
+"#,
+		)
+		.unwrap();
+		let doc = PrDoc::try_from(&value).unwrap();
+
+		assert_eq!(None, doc.doc[0].audience);
+		assert_eq!("bar", doc.doc[0].description);
+		assert_eq!("", doc.crates[0].name);
+		assert_eq!(Some(SemverLevel::Patch), doc.crates[0].semver);
+		assert!(Value::from(&doc)["doc"][0].get("audience").is_none());
+	}
+}
@@ -5,7 +5,7 @@ use serde::Serialize;
 use std::path::PathBuf;
 
 /// This enum defines the initial input used to find a PRDoc.
-#[derive(Debug, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub enum PRDocSource {
 	/// Filename of the PRDoc
 	File(PathBuf),
@@ -0,0 +1,375 @@
+//! Reports of the check command in formats understood by CI systems:
+//! - [SARIF](https://sarifweb.azurewebsites.net/) for code scanning tools such as GitHub
+//! - JUnit XML for the test views of Jenkins, GitLab and others
+//! - JSON for custom tooling
+//! - [workflow commands](https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions)
+//!   showing the problems inline on the diff of GitHub pull requests
+
+use crate::{
+	commands::check::{CheckErrorKind, CheckReport},
+	diagnostic::{Diagnostic, Severity},
+	lint::{builtin_rules, LINT_PREFIX},
+	prdoc_source::PRDocSource,
+	utils::get_project_root,
+};
+use serde_json::{json, Value};
+use std::{
+	collections::BTreeMap,
+	env,
+	path::{Path, PathBuf},
+};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Return a readable name for the PRDoc of a report
+fn name(report: &CheckReport, root: Option<&Path>) -> String {
+	match (&report.file, &report.source) {
+		(Some(file), _) => relative_to(file, root),
+		(None, PRDocSource::File(file) | PRDocSource::Both(file, _)) => relative_to(file, root),
+		(None, PRDocSource::Number(number)) => format!("PR #{number}"),
+	}
+}
+
+/// Return the root of the repository the paths of the reports are relative to, as expected by the
+/// tools consuming them. The root is `GITHUB_WORKSPACE` in GitHub Actions, the project root
+/// otherwise. It is resolved once per report since finding the project root walks the filesystem.
+fn root() -> Option<PathBuf> {
+	env::var_os("GITHUB_WORKSPACE")
+		.filter(|workspace| !workspace.is_empty())
+		.map(PathBuf::from)
+		.or_else(|| get_project_root().ok())
+}
+
+/// Return the path of a file relative to `root` when it is within it. Relative paths are
+/// considered from the current directory, which may be a subfolder of the `root`.
+fn relative_to(file: &Path, root: Option<&Path>) -> String {
+	let absolute = env::current_dir().map(|dir| dir.join(file)).unwrap_or(file.to_path_buf());
+	let relative = root.and_then(|root| absolute.strip_prefix(root).ok());
+	relative.unwrap_or(file).display().to_string().replace('\\', "/")
+}
+
+/// Return the diagnostics of a report, including the problems that are only described by a
+/// message
+fn diagnostics(report: &CheckReport, root: Option<&Path>) -> Vec<Diagnostic> {
+	let code = report.error.as_ref().map(CheckErrorKind::code).unwrap_or("prdoc/check");
+	let messages = report.messages.iter().map(|message| Diagnostic {
+		severity: if report.is_valid() { Severity::Warning } else { Severity::Error },
+		code: code.into(),
+		message: format!("{}: {message}", name(report, root)),
+		file: report.file.clone().unwrap_or_default(),
+		path: String::new(),
+		range: None,
+		snippet: None,
+		hint: None,
+	});
+	messages.chain(report.diagnostics.iter().cloned()).collect()
+}
+
+/// Build a JSON report: one entry per checked PRDoc with its status and diagnostics
+pub fn json(reports: &[CheckReport]) -> Value {
+	serde_json::to_value(reports).expect("We can serialize the reports")
+}
+
+/// Build a SARIF 2.1.0 report containing all the diagnostics
+pub fn sarif(reports: &[CheckReport]) -> Value {
+	let root = root();
+	let diagnostics: Vec<Diagnostic> =
+		reports.iter().flat_map(|report| diagnostics(report, root.as_deref())).collect();
+
+	let descriptions: BTreeMap<String, &str> = builtin_rules()
+		.iter()
+		.map(|rule| (format!("{LINT_PREFIX}{}", rule.id()), rule.description()))
+		.collect();
+	let codes: BTreeMap<&str, String> = diagnostics
+		.iter()
+		.map(|d| {
+			let description = match descriptions.get(&d.code) {
+				Some(description) => description.to_string(),
+				None if d.code.starts_with("prdoc/") =>
+					"The PRDoc could not be checked".to_string(),
+				None => format!("Schema validation: {}", d.code),
+			};
+			(d.code.as_str(), description)
+		})
+		.collect();
+	let rules: Vec<Value> = codes
+		.iter()
+		.map(|(id, description)| json!({ "id": id, "shortDescription": { "text": description } }))
+		.collect();
+
+	let sarif_results: Vec<Value> = diagnostics
+		.iter()
+		.map(|d| {
+			let mut message = d.message.clone();
+			if let Some(hint) = &d.hint {
+				message = format!("{message}\n{}", hint.trim());
+			}
+
+			let mut location = json!({
+				"physicalLocation": { "artifactLocation": { "uri": relative_to(&d.file, root.as_deref()) } }
+			});
+			if let Some(range) = d.range {
+				location["physicalLocation"]["region"] = json!({
+					"startLine": range.start.line,
+					"startColumn": range.start.column,
+					"endLine": range.end.line,
+					"endColumn": range.end.column,
+				});
+			}
+
+			json!({
+				"ruleId": d.code,
+				"ruleIndex": codes.keys().position(|code| *code == d.code),
+				"level": d.severity.to_string(),
+				"message": { "text": message },
+				"locations": if d.file.as_os_str().is_empty() { vec![] } else { vec![location] },
+			})
+		})
+		.collect();
+
+	json!({
+		"$schema": SARIF_SCHEMA,
+		"version": "2.1.0",
+		"runs": [{
+			"tool": {
+				"driver": {
+					"name": "prdoc",
+					"informationUri": "https://github.com/paritytech/prdoc",
+					"version": env!("CARGO_PKG_VERSION"),
+					"rules": rules,
+				}
+			},
+			"results": sarif_results,
+		}]
+	})
+}
+
+/// Build a JUnit XML report with a test case per checked file. The errors are reported as
+/// failures, the warnings are only mentioned in the output of the test case unless they are
+/// denied.
+pub fn junit(reports: &[CheckReport]) -> String {
+	let root = root();
+	let failures = reports.iter().filter(|report| !report.is_valid()).count();
+	let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+	xml.push_str(&format!(
+		"<testsuites name=\"prdoc\" tests=\"{}\" failures=\"{failures}\">\n",
+		reports.len()
+	));
+	xml.push_str(&format!(
+		"  <testsuite name=\"prdoc check\" tests=\"{}\" failures=\"{failures}\">\n",
+		reports.len()
+	));
+
+	for report in reports {
+		let diagnostics = diagnostics(report, root.as_deref());
+		xml.push_str(&format!(
+			"    <testcase classname=\"prdoc\" name=\"{}\"",
+			escape(&name(report, root.as_deref()))
+		));
+
+		if diagnostics.is_empty() {
+			xml.push_str("/>\n");
+			continue;
+		}
+		xml.push_str(">\n");
+
+		let denied = report.error == Some(CheckErrorKind::Warnings);
+		let (failures, warnings): (Vec<_>, Vec<_>) =
+			diagnostics.iter().partition(|d| d.severity == Severity::Error || denied);
+		for d in failures {
+			xml.push_str(&format!(
+				"      <failure type=\"{}\" message=\"{}\">{}</failure>\n",
+				escape(&d.code),
+				escape(&d.message),
+				escape(&d.to_string())
+			));
+		}
+
+		let warnings: String = warnings.iter().map(|d| d.to_string()).collect();
+		if !warnings.is_empty() {
+			xml.push_str(&format!("      <system-out>{}</system-out>\n", escape(&warnings)));
+		}
+		xml.push_str("    </testcase>\n");
+	}
+
+	xml.push_str("  </testsuite>\n</testsuites>\n");
+	xml
+}
+
+/// Build the GitHub Actions workflow commands annotating the diagnostics: `::error` for the
+/// errors and `::warning` for the warnings, one per line
+pub fn github(reports: &[CheckReport]) -> String {
+	let root = root();
+	let mut output = String::new();
+	for d in reports.iter().flat_map(|report| diagnostics(report, root.as_deref())) {
+		let mut properties = vec![];
+		if !d.file.as_os_str().is_empty() {
+			properties
+				.push(format!("file={}", escape_property(&relative_to(&d.file, root.as_deref()))));
+		}
+		if let Some(range) = d.range {
+			properties.push(format!("line={}", range.start.line));
+			properties.push(format!("col={}", range.start.column));
+			properties.push(format!("endLine={}", range.end.line));
+			properties.push(format!("endColumn={}", range.end.column));
+		}
+		properties.push(format!("title={}", escape_property(&d.code)));
+
+		let mut message = d.message.clone();
+		if let Some(hint) = &d.hint {
+			message = format!("{message}\n{}", hint.trim());
+		}
+		output.push_str(&format!(
+			"::{} {}::{}\n",
+			d.severity,
+			properties.join(","),
+			escape_data(&message)
+		));
+	}
+	output
+}
+
+/// Escape the message of a workflow command
+fn escape_data(text: &str) -> String {
+	text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escape a property of a workflow command
+fn escape_property(text: &str) -> String {
+	escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Escape the XML special characters
+fn escape(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test_report {
+	use super::*;
+	use crate::source_map::SourceMap;
+	use std::path::PathBuf;
+
+	fn results() -> Vec<CheckReport> {
+		let source = "title: <Foo>\ncrates:\n  - name: foo\n";
+		let file = PathBuf::from("prdoc/pr_1.prdoc");
+		let map = SourceMap::parse(source);
+		let diagnostic = |severity, code: &str, path: &str| {
+			Diagnostic::new(severity, code, "Something & else", &file, path, source, map.as_ref())
+		};
+
+		vec![
+			CheckReport::new(
+				PRDocSource::File(PathBuf::from("pr_1.prdoc")),
+				file.clone(),
+				vec![
+					diagnostic(Severity::Error, "schema/required", "/doc"),
+					diagnostic(Severity::Warning, "lint/major-bump-note", "/crates/0"),
+				],
+			),
+			CheckReport::new(
+				PRDocSource::File(PathBuf::from("pr_2.prdoc")),
+				PathBuf::from("prdoc/pr_2.prdoc"),
+				vec![],
+			),
+			CheckReport::failed(
+				PRDocSource::Number(3),
+				None,
+				CheckErrorKind::NotFound,
+				"Not found",
+			),
+		]
+	}
+
+	#[test]
+	fn test_sarif() {
+		let report = sarif(&results());
+		let run = &report["runs"][0];
+
+		let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+		assert_eq!(
+			vec!["lint/major-bump-note", "prdoc/not-found", "schema/required"],
+			rules.iter().map(|r| r["id"].as_str().unwrap()).collect::<Vec<_>>()
+		);
+
+		let results = run["results"].as_array().unwrap();
+		assert_eq!(3, results.len());
+		assert_eq!("schema/required", results[0]["ruleId"]);
+		assert_eq!(2, results[0]["ruleIndex"]);
+		assert_eq!("error", results[0]["level"]);
+		let location = &results[0]["locations"][0]["physicalLocation"];
+		assert_eq!("prdoc/pr_1.prdoc", location["artifactLocation"]["uri"]);
+		assert_eq!(1, location["region"]["startLine"]);
+		assert_eq!("warning", results[1]["level"]);
+		assert_eq!(3, results[1]["locations"][0]["physicalLocation"]["region"]["startLine"]);
+		assert_eq!("prdoc/not-found", results[2]["ruleId"]);
+		assert!(results[2]["locations"].as_array().unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_junit() {
+		let report = junit(&results());
+
+		assert!(report.contains("<testsuites name=\"prdoc\" tests=\"3\" failures=\"2\">"));
+		assert!(report.contains("<testcase classname=\"prdoc\" name=\"prdoc/pr_1.prdoc\">"));
+		assert!(
+			report.contains("<failure type=\"schema/required\" message=\"Something &amp; else\">")
+		);
+		assert!(report.contains("<system-out>warning[lint/major-bump-note]"));
+		assert!(report.contains("<testcase classname=\"prdoc\" name=\"prdoc/pr_2.prdoc\"/>"));
+		assert!(report.contains("<failure type=\"prdoc/not-found\" message=\"PR #3: Not found\">"));
+
+		// Once denied, the warnings of valid files become failures
+		let mut warned = results().remove(0);
+		warned.diagnostics.retain(|d| d.severity == Severity::Warning);
+		let warned = CheckReport::new(warned.source, warned.file.unwrap(), warned.diagnostics);
+		let report = junit(&[warned.deny_warnings()]);
+		assert!(report.contains("<failure type=\"lint/major-bump-note\""));
+		assert!(!report.contains("<system-out>"));
+	}
+
+	#[test]
+	fn test_github() {
+		let report = github(&results());
+		let lines: Vec<&str> = report.lines().collect();
+
+		assert_eq!(3, lines.len());
+		assert_eq!(
+			"::error file=prdoc/pr_1.prdoc,line=1,col=1,endLine=3,endColumn=14,title=schema/required::Something & else",
+			lines[0]
+		);
+		assert!(lines[1].starts_with("::warning file=prdoc/pr_1.prdoc,line=3,"));
+		assert_eq!("::error title=prdoc/not-found::PR #3: Not found", lines[2]);
+		assert_eq!("a%0Ab%25", escape_data("a\nb%"));
+		assert_eq!("C%3A%2Cd", escape_property("C:,d"));
+	}
+
+	#[test]
+	fn test_relative() {
+		let file = Path::new("/repo/prdoc/pr_1.prdoc");
+		assert_eq!("prdoc/pr_1.prdoc", relative_to(file, Some(Path::new("/repo"))));
+		assert_eq!("/repo/prdoc/pr_1.prdoc", relative_to(file, Some(Path::new("/other"))));
+		assert_eq!("/repo/prdoc/pr_1.prdoc", relative_to(file, None));
+
+		// Relative paths are resolved from the current directory, wherever it is in the repository
+		let dir = env::current_dir().unwrap();
+		let expected = format!("{}/prdoc/pr_1.prdoc", dir.file_name().unwrap().to_string_lossy());
+		assert_eq!(expected, relative_to(Path::new("prdoc/pr_1.prdoc"), dir.parent()));
+	}
+
+	#[test]
+	fn test_json() {
+		let report = json(&results());
+		assert_eq!(3, report.as_array().unwrap().len());
+		assert_eq!("invalid", report[0]["status"]);
+		assert_eq!("schema", report[0]["error"]);
+		assert_eq!("schema/required", report[0]["diagnostics"][0]["code"]);
+		assert_eq!("valid", report[1]["status"]);
+		assert_eq!("not-found", report[2]["error"]);
+		assert_eq!("Not found", report[2]["messages"][0]);
+	}
+}
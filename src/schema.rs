@@ -4,15 +4,37 @@
 //! The schema is stored in the repository and embedded into the cli for convenience. The various
 //! commands do check that files comply with the schema but nothing more. That also means that the
 //! schema can be adjusted at any time without impact on the code.
+//!
+//! The official versions of the schema are embedded and can be referred to as `builtin:<version>`,
+//! ie `builtin:v1`. A schema file can be used instead of the embedded ones.
+//!
+//! A PRDoc file may declare the version of the schema it was written for in its header:
+//!
+//! ```yaml
+//! # Schema: Parity PR Documentation Schema (prdoc) v1.0.0
+//! ```
+//!
+//! Such a file is validated against the registered schema with the same major version. Files
+//! without declaration are validated against the current schema.
 
-use crate::error::PRdocLibError;
+use crate::{
+	diagnostic::{Diagnostic, Severity},
+	error::PRdocLibError,
+	model::PrDoc,
+};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::{
-	fs::{self, File},
+	cmp::Ordering,
+	convert::Infallible,
+	fmt::Display,
+	fs,
 	path::{Path, PathBuf},
+	str::FromStr,
+	sync::{Arc, LazyLock, Mutex},
 };
-use valico::json_schema;
+use valico::json_schema::{self, ValidationState};
 
 /// Default file extension
 pub const EXTENSION: &str = "prdoc";
@@ -20,16 +42,363 @@ pub const EXTENSION: &str = "prdoc";
 /// Default location where prdoc are stored
 pub const PRDOC_DEFAULT_DIR: &str = "prdoc";
 
+/// Prefix used to refer to a schema embedded in the cli
+pub const BUILTIN_PREFIX: &str = "builtin:";
+
+/// A schema embedded in the cli
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinSchema {
+	/// Version used to refer to the schema, ie `v1`
+	pub version: &'static str,
+
+	/// The JSON definition of the schema
+	pub content: &'static str,
+}
+
+/// The schemas embedded in the cli, from the oldest to the latest
+pub const BUILTIN_SCHEMAS: &[BuiltinSchema] = &[BuiltinSchema {
+	version: "v1",
+	content: include_str!("../schemas/prdoc_schema_user_v1.json"),
+}];
+
+impl BuiltinSchema {
+	/// Return the latest embedded schema
+	pub fn latest() -> &'static BuiltinSchema {
+		BUILTIN_SCHEMAS.last().expect("There is at least one embedded schema")
+	}
+
+	/// Return the embedded schema matching `version`. Both `v1` and `1` are accepted.
+	pub fn get(version: &str) -> Option<&'static BuiltinSchema> {
+		let version = version.strip_prefix('v').unwrap_or(version);
+		BUILTIN_SCHEMAS.iter().find(|s| s.version.strip_prefix('v') == Some(version))
+	}
+
+	/// Return the name used to refer to the schema, ie `builtin:v1`
+	pub fn name(&self) -> String {
+		format!("{BUILTIN_PREFIX}{}", self.version)
+	}
+}
+
+/// Version of a schema, as declared by the `version` property of the schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct SchemaVersion {
+	/// Major version, increased with breaking changes
+	pub major: u64,
+
+	/// Minor version
+	pub minor: u64,
+
+	/// Patch version
+	pub patch: u64,
+}
+
+impl SchemaVersion {
+	/// Create a new version
+	pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+		Self { major, minor, patch }
+	}
+
+	/// Read the version from the `version` property of a JSON schema
+	pub fn from_schema(json: &serde_json::Value) -> Option<Self> {
+		let version = json.get("version")?;
+		let part = |name: &str| version.get(name).and_then(|n| n.as_u64());
+		Some(Self::new(part("major")?, part("minor").unwrap_or(0), part("patch").unwrap_or(0)))
+	}
+
+	/// Find the version declared in the header of a PRDoc file, ie:
+	/// `# Schema: Parity PR Documentation Schema (prdoc) v1.0.0`.
+	/// The line where the declaration was found is returned alongside, starting at 1.
+	pub fn declared_in(source: &str) -> Option<(Self, usize)> {
+		static DECLARATION: LazyLock<Regex> =
+			LazyLock::new(|| Regex::new(r"(?i)^#\s*schema:.*\sv(\d+(?:\.\d+){0,2})\s*$").unwrap());
+
+		source
+			.lines()
+			.enumerate()
+			// Only the comments at the top of the file are considered
+			.take_while(|(_, line)| line.trim().is_empty() || line.trim_start().starts_with('#'))
+			.find_map(|(index, line)| {
+				let version = DECLARATION.captures(line.trim())?.get(1)?.as_str().parse().ok()?;
+				Some((version, index + 1))
+			})
+	}
+}
+
+impl FromStr for SchemaVersion {
+	type Err = String;
+
+	/// Parse versions such as `v1`, `1.2` or `1.2.3`
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let parts = s
+			.strip_prefix('v')
+			.unwrap_or(s)
+			.split('.')
+			.map(|p| p.parse::<u64>())
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("Invalid version {s}: {e}"))?;
+
+		match parts[..] {
+			[major] => Ok(Self::new(major, 0, 0)),
+			[major, minor] => Ok(Self::new(major, minor, 0)),
+			[major, minor, patch] => Ok(Self::new(major, minor, patch)),
+			_ => Err(format!("Invalid version {s}")),
+		}
+	}
+}
+
+impl Display for SchemaVersion {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+	}
+}
+
+/// Where to find a schema: either embedded in the cli or as a file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum SchemaSource {
+	/// Version of a schema embedded in the cli, ie `v1`
+	Builtin(String),
+
+	/// Path of a schema file
+	File(PathBuf),
+}
+
+impl SchemaSource {
+	/// Load and compile the schema. Relative paths are resolved from `root`.
+	pub fn load(&self, root: &Path) -> crate::error::Result<Schema> {
+		match self {
+			SchemaSource::Builtin(version) => Schema::builtin(version),
+			SchemaSource::File(path) if path.is_absolute() => Schema::new(path),
+			SchemaSource::File(path) => Schema::new(root.join(path)),
+		}
+	}
+}
+
+impl Default for SchemaSource {
+	fn default() -> Self {
+		SchemaSource::Builtin(BuiltinSchema::latest().version.to_string())
+	}
+}
+
+impl FromStr for SchemaSource {
+	type Err = Infallible;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.strip_prefix(BUILTIN_PREFIX) {
+			Some(version) => SchemaSource::Builtin(version.to_string()),
+			None => SchemaSource::File(PathBuf::from(s)),
+		})
+	}
+}
+
+impl From<String> for SchemaSource {
+	fn from(s: String) -> Self {
+		Self::from_str(&s).expect("Infallible")
+	}
+}
+
+impl From<PathBuf> for SchemaSource {
+	fn from(path: PathBuf) -> Self {
+		SchemaSource::File(path)
+	}
+}
+
+impl From<SchemaSource> for String {
+	fn from(source: SchemaSource) -> Self {
+		source.to_string()
+	}
+}
+
+impl Display for SchemaSource {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			SchemaSource::Builtin(version) => write!(f, "{BUILTIN_PREFIX}{version}"),
+			SchemaSource::File(path) => write!(f, "{}", path.display()),
+		}
+	}
+}
+
 /// The schema embedded in [prdoc](/prdoc).
+///
+/// The schema is compiled once when created. Cloning a `Schema` is cheap and the same instance can
+/// be shared across threads to validate any number of files.
+///
+/// Besides the current schema, older versions of the schema can be registered with
+/// [Schema::with_supported] so that files declaring an older version remain valid.
 #[derive(Debug, Clone)]
 pub struct Schema {
-	schema: PathBuf,
+	inner: Arc<CompiledSchema>,
+
+	/// Other versions of the schema that are still supported
+	supported: Vec<Schema>,
+}
+
+/// The result of a successful validation
+#[derive(Debug, Clone)]
+pub struct ValidatedDoc {
+	/// The raw content of the PRDoc
+	pub value: Value,
+
+	/// The typed content of the PRDoc
+	pub prdoc: PrDoc,
+
+	/// The version of the schema used to validate the file, if the schema declares one
+	pub schema_version: Option<SchemaVersion>,
+
+	/// Warnings raised during the validation
+	pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug)]
+struct CompiledSchema {
+	/// The JSON definition of the schema
+	json: serde_json::Value,
+
+	/// The version declared by the schema
+	version: Option<SchemaVersion>,
+
+	/// Compiled validators ready to be used. The validators of `valico` cannot be used
+	/// concurrently so each thread validating a file borrows one from the pool. A new one is only
+	/// compiled when all the existing ones are in use.
+	pool: Mutex<Vec<Validator>>,
+}
+
+#[derive(Debug)]
+struct Validator {
+	/// The scope holding the compiled validators
+	scope: json_schema::Scope,
+
+	/// Id of the compiled schema in the `scope`
+	id: url::Url,
+}
+
+impl Validator {
+	fn compile(json: &serde_json::Value) -> crate::error::Result<Self> {
+		log::trace!("Compiling schema");
+		let mut scope = json_schema::Scope::new();
+		let id = scope.compile(json.clone(), false)?;
+		Ok(Self { scope, id })
+	}
+}
+
+impl CompiledSchema {
+	fn validate(&self, doc: &serde_json::Value) -> ValidationState {
+		let pooled = self.pool.lock().expect("The pool is never poisoned").pop();
+		let validator = match pooled {
+			Some(validator) => validator,
+			None => Validator::compile(&self.json).expect("The schema compiled before"),
+		};
+
+		let validation = validator
+			.scope
+			.resolve(&validator.id)
+			.expect("The schema was compiled in this scope")
+			.validate(doc);
+
+		self.pool.lock().expect("The pool is never poisoned").push(validator);
+		validation
+	}
 }
 
 impl Schema {
-	/// Create a new instance of the schema
-	pub fn new(schema: PathBuf) -> Self {
-		Self { schema }
+	/// Create a new instance of the schema by loading and compiling the schema file
+	pub fn new<P: AsRef<Path>>(schema: P) -> crate::error::Result<Self> {
+		log::trace!("Loading schema file");
+		let content = fs::read_to_string(schema)?;
+		Self::from_json_str(&content)
+	}
+
+	/// Create a new instance of one of the schemas embedded in the cli
+	pub fn builtin(version: &str) -> crate::error::Result<Self> {
+		let builtin = BuiltinSchema::get(version)
+			.ok_or_else(|| PRdocLibError::UnknownSchema(format!("{BUILTIN_PREFIX}{version}")))?;
+		Self::from_json_str(builtin.content)
+	}
+
+	/// Create a new instance of the schema from its JSON definition
+	pub fn from_json_str(content: &str) -> crate::error::Result<Self> {
+		let schema_str = Self::get(content.to_string(), true);
+
+		log::trace!("Parsing schema");
+		let json: serde_json::Value = serde_json::from_str(&schema_str)?;
+		let validator = Validator::compile(&json)?;
+		let version = SchemaVersion::from_schema(&json);
+		let pool = Mutex::new(vec![validator]);
+
+		Ok(Self { inner: Arc::new(CompiledSchema { json, version, pool }), supported: vec![] })
+	}
+
+	/// Register other versions of the schema that are still supported. Schemas without version
+	/// or with a version that is already registered are ignored.
+	pub fn with_supported(mut self, schemas: impl IntoIterator<Item = Schema>) -> Self {
+		for schema in schemas {
+			let Some(version) = schema.version() else { continue };
+			if !self.versions().contains(&version) {
+				self.supported.push(Self { supported: vec![], ..schema });
+			}
+		}
+		self
+	}
+
+	/// Return the JSON definition of the schema
+	pub fn json(&self) -> &serde_json::Value {
+		&self.inner.json
+	}
+
+	/// Return the version declared by the schema
+	pub fn version(&self) -> Option<SchemaVersion> {
+		self.inner.version
+	}
+
+	/// Return the versions of all the registered schemas, starting with the current one
+	pub fn versions(&self) -> Vec<SchemaVersion> {
+		self.version()
+			.into_iter()
+			.chain(self.supported.iter().filter_map(|s| s.version()))
+			.collect()
+	}
+
+	/// Find the schema to use for a file declaring the `declared` version. Files are validated
+	/// against the schema with the same major version. An older major version is still accepted
+	/// if it is registered. Any version other than the current one comes with a warning.
+	fn select(
+		&self,
+		declared: SchemaVersion,
+	) -> std::result::Result<(&Schema, Option<String>), String> {
+		let Some(current) = self.version() else {
+			// Without version, the schema cannot tell which files it supports
+			return Ok((self, None));
+		};
+
+		let warning = match declared.cmp(&current) {
+			Ordering::Less => Some(format!(
+				"The file declares the schema {declared} which is older than the current schema \
+				 {current}. Consider running `prdoc migrate`."
+			)),
+			Ordering::Greater => Some(format!(
+				"The file declares the schema {declared} which is newer than the current schema \
+				 {current}. Consider updating prdoc or the schema of the config."
+			)),
+			Ordering::Equal => None,
+		};
+		if declared.major == current.major {
+			return Ok((self, warning));
+		}
+
+		let supported = self
+			.supported
+			.iter()
+			.filter(|s| s.version().is_some_and(|v| v.major == declared.major))
+			.max_by_key(|s| s.version());
+
+		match supported {
+			Some(schema) => Ok((schema, warning)),
+			None => Err(format!(
+				"The file declares the schema {declared} which is not supported. Supported \
+				 versions: {}",
+				self.versions().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+			)),
+		}
 	}
 
 	/// JSON Schema sometimes do contain comments. This function strips them to allow
@@ -45,41 +414,94 @@ impl Schema {
 	}
 
 	/// Check the validity of a file by attempting to load it
-	pub fn check_file(&self, file: &PathBuf) -> bool {
+	pub fn check_file<P: AsRef<Path>>(&self, file: &P) -> bool {
 		self.load(file).is_ok()
 	}
 
 	/// Load the content of a file. The name does not matter here.
-	pub fn load<P: AsRef<Path>>(&self, file: &P) -> crate::error::Result<Value> {
-		log::trace!("Loading schema file");
-		let content = fs::read_to_string(self.schema.clone())?.parse()?;
-		let schema_str = Self::get(content, true);
+	/// On success, both the raw content and its typed representation are returned.
+	pub fn load<P: AsRef<Path>>(&self, file: &P) -> crate::error::Result<(Value, PrDoc)> {
+		let source = fs::read_to_string(file)?;
+		self.load_str(file.as_ref(), &source)
+	}
 
-		log::trace!("Parsing schema");
-		let json_schema: serde_json::Value = serde_json::from_str(&schema_str)?;
+	/// Validate the `source` of a PRDoc. The `file` is only used to report diagnostics.
+	pub fn load_str(&self, file: &Path, source: &str) -> crate::error::Result<(Value, PrDoc)> {
+		let validated = self.validate_str(file, source)?;
+		for diagnostic in &validated.diagnostics {
+			log::warn!("{}: {}", file.display(), diagnostic.message);
+		}
+		Ok((validated.value, validated.prdoc))
+	}
 
-		let reader = File::open(file)?;
-		let mut doc_as_yaml: serde_yaml::Value = serde_yaml::from_reader(reader)?;
+	/// Validate a file against the schema matching the version it declares.
+	/// Unlike [Schema::load], the warnings are returned instead of being logged.
+	pub fn validate<P: AsRef<Path>>(&self, file: &P) -> crate::error::Result<ValidatedDoc> {
+		let source = fs::read_to_string(file)?;
+		self.validate_str(file.as_ref(), &source)
+	}
+
+	/// Validate the `source` of a PRDoc. The `file` is only used to report diagnostics.
+	pub fn validate_str(&self, file: &Path, source: &str) -> crate::error::Result<ValidatedDoc> {
+		let mut doc_as_yaml: serde_yaml::Value = serde_yaml::from_str(source)?;
 		doc_as_yaml.apply_merge()?;
 
-		let doc_as_json: serde_json::Value =
-			serde_yaml::from_value(serde_yaml::to_value(&doc_as_yaml)?)?;
+		let doc_as_json = serde_json::to_value(&doc_as_yaml)?;
 
-		let mut scope = json_schema::Scope::new();
-		let schema = scope.compile_and_return(json_schema, false)?;
+		let mut diagnostics = vec![];
+		let schema = match SchemaVersion::declared_in(source) {
+			None => self,
+			Some((declared, line)) => {
+				let code = "schema/version";
+				let diagnostic = |severity, message| {
+					Diagnostic::new(severity, code, message, file, "", source, None)
+						.at_line(source, line)
+				};
+
+				match self.select(declared) {
+					Ok((schema, warning)) => {
+						diagnostics.extend(warning.map(|w| diagnostic(Severity::Warning, w)));
+						schema
+					},
+					Err(error) => {
+						let diagnostic = diagnostic(Severity::Error, error);
+						return Err(PRdocLibError::ValidationErrors(vec![diagnostic]));
+					},
+				}
+			},
+		};
 
-		log::trace!("Validate file with schema");
-		let validation = schema.validate(&doc_as_json);
+		log::trace!("Validate file with schema {:?}", schema.version());
+		let validation = schema.inner.validate(&doc_as_json);
 		let validation_result = validation.is_valid();
 		let validation_result_strict = validation.is_strictly_valid();
 
 		if !(validation_result && validation_result_strict) {
 			log::debug!("validation_result: {validation_result}");
 			log::debug!("validation_result_strict: {validation_result_strict}");
-			return Err(PRdocLibError::ValidationErrors(Box::new(validation)));
+			diagnostics.extend(Diagnostic::from_validation(
+				file,
+				source,
+				&schema.inner.json,
+				&validation,
+			));
+			return Err(PRdocLibError::ValidationErrors(diagnostics));
 		}
 
-		Ok(doc_as_yaml)
+		diagnostics.extend(Diagnostic::from_deprecated(
+			file,
+			source,
+			&schema.inner.json,
+			&doc_as_json,
+		));
+
+		let prdoc = PrDoc::try_from(&doc_as_yaml)?;
+		Ok(ValidatedDoc {
+			value: doc_as_yaml,
+			prdoc,
+			schema_version: schema.version(),
+			diagnostics,
+		})
 	}
 }
 
@@ -88,18 +510,179 @@ mod test_schema_validation {
 	use super::*;
 	use std::path::PathBuf;
 
+	fn assert_send_sync<T: Send + Sync>() {}
+
+	#[test]
+	fn test_schema_is_send_sync() {
+		assert_send_sync::<Schema>();
+	}
+
 	#[test]
 	fn test_load_valid_1234() {
-		let schema = Schema::new("./tests/data/sample_schema.json".into());
+		let schema = Schema::new("./tests/data/sample_schema.json").unwrap();
 		let file = PathBuf::from("./tests/data/some/pr_1234_some_test_minimal.prdoc");
-		assert!(schema.load(&file).is_ok());
+		let (_value, prdoc) = schema.load(&file).unwrap();
+		assert_eq!("Foobar", prdoc.title);
+		assert_eq!(3, prdoc.crates.len());
 	}
 
 	#[test]
 	fn test_check_valid_1234() {
-		let schema = Schema::new("./tests/data/sample_schema.json".into());
+		let schema = Schema::new("./tests/data/sample_schema.json").unwrap();
 
 		let file = PathBuf::from("./tests/data/some/pr_1234_some_test_minimal.prdoc");
 		assert!(schema.check_file(&file));
 	}
+
+	#[test]
+	fn test_builtin() {
+		assert!(BuiltinSchema::get("v1").is_some());
+		assert_eq!(BuiltinSchema::get("1"), BuiltinSchema::get("v1"));
+		assert!(BuiltinSchema::get("v0").is_none());
+
+		let schema = Schema::builtin(BuiltinSchema::latest().version).unwrap();
+		let file = PathBuf::from("./tests/data/some/pr_1234_some_test_minimal.prdoc");
+		assert!(schema.check_file(&file));
+	}
+
+	#[test]
+	fn test_schema_source() {
+		assert_eq!(SchemaSource::Builtin("v1".into()), "builtin:v1".parse().unwrap());
+		assert_eq!(SchemaSource::File("schema.json".into()), "schema.json".parse().unwrap());
+		assert_eq!("builtin:v1", SchemaSource::Builtin("v1".into()).to_string());
+		assert!(SchemaSource::Builtin("v42".into()).load(Path::new(".")).is_err());
+		assert!(SchemaSource::File("tests/data/sample_schema.json".into())
+			.load(Path::new("."))
+			.is_ok());
+	}
+
+	#[test]
+	fn test_schema_is_reused() {
+		let schema = Schema::new("./tests/data/sample_schema.json").unwrap();
+		let other = schema.clone();
+
+		let valid = PathBuf::from("./tests/data/some/pr_1234_some_test_minimal.prdoc");
+		let invalid = PathBuf::from("./tests/data/some/pr_1_invalid.prdoc");
+		assert!(schema.check_file(&valid));
+		assert!(!other.check_file(&invalid));
+		assert!(other.check_file(&valid));
+	}
+
+	#[test]
+	fn test_concurrent_validation() {
+		let schema = Schema::new("./tests/data/sample_schema.json").unwrap();
+		let valid = PathBuf::from("./tests/data/some/pr_1234_some_test_minimal.prdoc");
+
+		std::thread::scope(|s| {
+			for _ in 0..4 {
+				s.spawn(|| assert!(schema.check_file(&valid)));
+			}
+		});
+	}
+
+	/// Build a copy of the latest embedded schema declaring another major version
+	fn schema_with_major(major: u64) -> Schema {
+		schema_with_version(major, 0)
+	}
+
+	/// Build a copy of the latest embedded schema declaring another version
+	fn schema_with_version(major: u64, minor: u64) -> Schema {
+		let mut json: serde_json::Value =
+			serde_json::from_str(BuiltinSchema::latest().content).unwrap();
+		json["version"]["major"] = major.into();
+		json["version"]["minor"] = minor.into();
+		Schema::from_json_str(&json.to_string()).unwrap()
+	}
+
+	fn minimal_with_header(version: &str) -> String {
+		let content =
+			fs::read_to_string("./tests/data/some/pr_1234_some_test_minimal.prdoc").unwrap();
+		format!("# Schema: Parity PR Documentation Schema (prdoc) {version}\n\n{content}")
+	}
+
+	#[test]
+	fn test_version_declared_in() {
+		let source =
+			"# Schema: Parity PR Documentation Schema (prdoc) v1.0.0\n# See doc\ntitle: Foo\n";
+		assert_eq!(Some((SchemaVersion::new(1, 0, 0), 1)), SchemaVersion::declared_in(source));
+		assert_eq!(
+			Some((SchemaVersion::new(2, 1, 0), 2)),
+			SchemaVersion::declared_in("\n# schema: prdoc v2.1\n")
+		);
+		assert_eq!(None, SchemaVersion::declared_in("title: Foo\n# Schema: prdoc v1.0.0\n"));
+		assert_eq!(None, SchemaVersion::declared_in("title: Foo\n"));
+	}
+
+	#[test]
+	fn test_validate_declared_version() {
+		let v1 = Schema::builtin("v1").unwrap();
+		let v2 = schema_with_major(2).with_supported([v1.clone()]);
+		assert_eq!(vec![SchemaVersion::new(2, 0, 0), SchemaVersion::new(1, 0, 0)], v2.versions());
+
+		let file = Path::new("pr_1234.prdoc");
+		let validated = v1.validate_str(file, &minimal_with_header("v1.0.0")).unwrap();
+		assert!(validated.diagnostics.is_empty());
+
+		let validated = v2.validate_str(file, &minimal_with_header("v1.0.0")).unwrap();
+		assert_eq!(Some(SchemaVersion::new(1, 0, 0)), validated.schema_version);
+		assert_eq!(1, validated.diagnostics.len());
+		assert_eq!(Severity::Warning, validated.diagnostics[0].severity);
+		assert_eq!(1, validated.diagnostics[0].range.unwrap().start.line);
+
+		// Files without declaration are validated against the current schema
+		let source =
+			fs::read_to_string("./tests/data/some/pr_1234_some_test_minimal.prdoc").unwrap();
+		let validated = v2.validate_str(file, &source).unwrap();
+		assert_eq!(Some(SchemaVersion::new(2, 0, 0)), validated.schema_version);
+		assert!(validated.diagnostics.is_empty());
+
+		// An older minor version of the current schema also deserves a warning
+		let v1_1 = schema_with_version(1, 1);
+		let validated = v1_1.validate_str(file, &minimal_with_header("v1.0.0")).unwrap();
+		assert_eq!(Some(SchemaVersion::new(1, 1, 0)), validated.schema_version);
+		assert_eq!(1, validated.diagnostics.len());
+		assert_eq!(Severity::Warning, validated.diagnostics[0].severity);
+		assert!(v1_1
+			.validate_str(file, &minimal_with_header("v1.1"))
+			.unwrap()
+			.diagnostics
+			.is_empty());
+
+		// So does a newer minor or patch version, validated against the current schema
+		for newer in ["v1.2", "v1.1.1"] {
+			let validated = v1_1.validate_str(file, &minimal_with_header(newer)).unwrap();
+			assert_eq!(Some(SchemaVersion::new(1, 1, 0)), validated.schema_version);
+			assert_eq!(1, validated.diagnostics.len());
+			assert_eq!(Severity::Warning, validated.diagnostics[0].severity);
+			assert!(validated.diagnostics[0].message.contains("newer"));
+		}
+	}
+
+	#[test]
+	fn test_validate_optional_fields() {
+		let v1 = Schema::builtin("v1").unwrap();
+		let source = fs::read_to_string("./tests/data/some/pr_1234_some_test_minimal.prdoc")
+			.unwrap()
+			.replace("  - audience: Node Dev\n    description", "  - description")
+			.replace("  - name: sp-foobar-b\n    semver", "  - semver");
+
+		let validated = v1.validate_str(Path::new("pr_1234.prdoc"), &source).unwrap();
+		assert_eq!(None, validated.prdoc.doc[0].audience);
+		assert_eq!("", validated.prdoc.crates[1].name);
+	}
+
+	#[test]
+	fn test_validate_unsupported_version() {
+		let v1 = Schema::builtin("v1").unwrap();
+		let file = Path::new("pr_1234.prdoc");
+
+		match v1.validate_str(file, &minimal_with_header("v3.0.0")) {
+			Err(PRdocLibError::ValidationErrors(diagnostics)) => {
+				assert_eq!(1, diagnostics.len());
+				assert_eq!(Severity::Error, diagnostics[0].severity);
+				assert!(diagnostics[0].message.contains("v3.0.0"));
+			},
+			other => panic!("Unexpected result: {other:?}"),
+		}
+	}
 }
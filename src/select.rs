@@ -0,0 +1,273 @@
+//! Extraction of values from the loaded PRDoc using a small subset of the JSONPath and jq syntax,
+//! so that scripts do not need an extra tool to, for instance, get the descriptions written for an
+//! audience.
+//!
+//! An expression is applied to the JSON representation of a [DocFileWrapper] and is made of:
+//! - `.name`, `."name"` or `["name"]`: the property `name` of an object
+//! - `[]`: all the elements of an array or the values of an object
+//! - `[n]`: the element `n` of an array, counted from the end if negative
+//! - `[?path == value]` or `[?path != value]`: the elements of an array whose property at `path`,
+//!   such as `audience` or `doc_filename.number`, is equal or not to the JSON `value`
+//!
+//! For instance `.content.doc[?audience == "Runtime User"].description`. The properties or
+//! elements that do not exist are skipped instead of being reported as errors.
+
+use crate::{
+	docfile_wrapper::DocFileWrapper,
+	error::{self, PRdocLibError},
+};
+use serde_json::Value;
+use std::{iter::Peekable, str::Chars};
+
+/// A step of a [Selector]
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+	/// The value of a property
+	Key(String),
+
+	/// All the elements of an array or the values of an object
+	Iter,
+
+	/// An element of an array
+	Index(i64),
+
+	/// The elements of an array whose property at `path` is equal, or not, to `value`
+	Filter { path: Vec<String>, value: Value, equal: bool },
+}
+
+impl Step {
+	fn apply<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+		match (self, value) {
+			(Step::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+			(Step::Iter, Value::Array(array)) => array.iter().collect(),
+			(Step::Iter, Value::Object(map)) => map.values().collect(),
+			(Step::Index(index), Value::Array(array)) => {
+				let index = if *index < 0 { array.len() as i64 + index } else { *index };
+				usize::try_from(index)
+					.ok()
+					.and_then(|index| array.get(index))
+					.into_iter()
+					.collect()
+			},
+			(Step::Filter { path, value, equal }, Value::Array(array)) => array
+				.iter()
+				.filter(|item| {
+					let found = path.iter().try_fold(*item, |item, key| item.get(key));
+					(found == Some(value)) == *equal
+				})
+				.collect(),
+			_ => vec![],
+		}
+	}
+}
+
+/// A parsed expression, see the [module](self) documentation for the syntax
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+	steps: Vec<Step>,
+}
+
+impl Selector {
+	/// Parse an expression such as `.content.crates[].name`
+	pub fn parse(expr: &str) -> error::Result<Self> {
+		let error = |msg: &str| PRdocLibError::InvalidExpression(expr.to_string(), msg.to_string());
+
+		let mut chars = expr.trim().chars().peekable();
+		if chars.peek().is_none() {
+			return Err(error("the expression is empty"));
+		}
+
+		let mut steps = vec![];
+		while let Some(c) = chars.next() {
+			match c {
+				'.' => match chars.peek() {
+					// `.` alone is the whole document
+					None | Some('[') => {},
+					Some('"') => {
+						chars.next();
+						let key =
+							take_string(&mut chars).ok_or_else(|| error("unterminated string"))?;
+						steps.push(Step::Key(key));
+					},
+					Some(_) => {
+						let key = take_ident(&mut chars);
+						if key.is_empty() {
+							return Err(error("expected a property name after `.`"));
+						}
+						steps.push(Step::Key(key));
+					},
+				},
+				'[' => {
+					let inner = take_bracket(&mut chars).ok_or_else(|| error("missing `]`"))?;
+					steps.push(parse_bracket(inner.trim()).map_err(|msg| error(&msg))?);
+				},
+				other => return Err(error(&format!("unexpected `{other}`, expected `.` or `[`"))),
+			}
+		}
+
+		Ok(Self { steps })
+	}
+
+	/// Return the values selected in `value`, in the order they appear
+	pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+		self.steps.iter().fold(vec![value], |values, step| {
+			values.into_iter().flat_map(|value| step.apply(value)).collect()
+		})
+	}
+
+	/// Return the values selected in each of the `wrappers`, as they appear in the output of the
+	/// load command
+	pub fn select_all(&self, wrappers: &[DocFileWrapper]) -> error::Result<Vec<Value>> {
+		let mut values = vec![];
+		for wrapper in wrappers {
+			let wrapper = serde_json::to_value(wrapper)?;
+			values.extend(self.select(&wrapper).into_iter().cloned());
+		}
+		Ok(values)
+	}
+}
+
+/// Take a property name, made of alphanumeric characters, `_` and `-`
+fn take_ident(chars: &mut Peekable<Chars>) -> String {
+	let mut ident = String::new();
+	while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '-') {
+		ident.push(c);
+	}
+	ident
+}
+
+/// Take a string up to the closing quote, the opening quote being already consumed
+fn take_string(chars: &mut Peekable<Chars>) -> Option<String> {
+	let mut raw = String::from('"');
+	let mut escaped = false;
+	for c in chars.by_ref() {
+		raw.push(c);
+		match c {
+			'"' if !escaped => return serde_json::from_str(&raw).ok(),
+			'\\' => escaped = !escaped,
+			_ => escaped = false,
+		}
+	}
+	None
+}
+
+/// Take the content of a bracket up to the closing `]`, ignoring the ones within strings
+fn take_bracket(chars: &mut Peekable<Chars>) -> Option<String> {
+	let mut inner = String::new();
+	let (mut quoted, mut escaped) = (false, false);
+	for c in chars.by_ref() {
+		match c {
+			']' if !quoted => return Some(inner),
+			'"' if !escaped => quoted = !quoted,
+			_ => {},
+		}
+		escaped = c == '\\' && !escaped;
+		inner.push(c);
+	}
+	None
+}
+
+fn parse_bracket(inner: &str) -> Result<Step, String> {
+	if inner.is_empty() {
+		return Ok(Step::Iter);
+	}
+
+	if let Some(filter) = inner.strip_prefix('?') {
+		// The path cannot contain `=` so the first operator is the one of the filter
+		let (path, value, equal) = match (filter.find("=="), filter.find("!=")) {
+			(Some(i), Some(j)) if j < i => (&filter[..j], &filter[j + 2..], false),
+			(Some(i), _) => (&filter[..i], &filter[i + 2..], true),
+			(None, Some(j)) => (&filter[..j], &filter[j + 2..], false),
+			(None, None) => return Err("expected `==` or `!=` in the filter".into()),
+		};
+
+		let path: Vec<String> =
+			path.trim().trim_start_matches('.').split('.').map(String::from).collect();
+		if path.iter().any(|key| key.is_empty()) {
+			return Err("expected a property before the operator of the filter".into());
+		}
+		let value = serde_json::from_str(value.trim())
+			.map_err(|_| format!("`{}` is not a valid JSON value", value.trim()))?;
+		return Ok(Step::Filter { path, value, equal });
+	}
+
+	if let Ok(index) = inner.parse::<i64>() {
+		return Ok(Step::Index(index));
+	}
+
+	match serde_json::from_str::<Value>(inner) {
+		Ok(Value::String(key)) => Ok(Step::Key(key)),
+		_ => Err(format!(
+			"`[{inner}]` is not valid, expected `[]`, `[n]`, `[\"name\"]` or `[?path == value]`"
+		)),
+	}
+}
+
+#[cfg(test)]
+mod test_select {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_parse() {
+		assert!(Selector::parse(".").unwrap().steps.is_empty());
+		assert_eq!(
+			vec![Step::Key("doc".into()), Step::Iter, Step::Index(-1), Step::Key("a b".into())],
+			Selector::parse(r#".doc[][-1]."a b""#).unwrap().steps
+		);
+		assert_eq!(
+			vec![Step::Filter {
+				path: vec!["a".into(), "b".into()],
+				value: json!("x]"),
+				equal: false
+			}],
+			Selector::parse(r#".[?a.b != "x]"]"#).unwrap().steps
+		);
+
+		for invalid in ["", "doc", ".doc[", ".[?a]", ".[?a == foo]", "..", ".[foo]", r#"."foo"#] {
+			assert!(Selector::parse(invalid).is_err(), "{invalid} should be invalid");
+		}
+	}
+
+	#[test]
+	fn test_select() {
+		let value = json!({
+			"doc": [
+				{ "audience": "Runtime User", "description": "a" },
+				{ "audience": "Node Dev", "description": "b" },
+			],
+			"crates": [{ "name": "foo", "semver": "major" }, { "name": "bar" }],
+		});
+		let select = |expr: &str| {
+			Selector::parse(expr)
+				.unwrap()
+				.select(&value)
+				.into_iter()
+				.cloned()
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(vec![value.clone()], select("."));
+		assert_eq!(vec![json!("a"), json!("b")], select(".doc[].description"));
+		assert_eq!(vec![json!("b")], select(".doc[-1].description"));
+		assert_eq!(vec![json!("a")], select(r#".doc[?audience == "Runtime User"].description"#));
+		assert_eq!(vec![json!("bar")], select(r#".crates[?semver != "major"].name"#));
+		assert!(select(".doc[5]").is_empty());
+		assert!(select(".missing[].name").is_empty());
+	}
+
+	#[test]
+	fn test_select_all() {
+		let content = std::fs::read_to_string("tests/data/all/pr_1225.prdoc").unwrap();
+		let value: serde_yaml::Value = serde_yaml::from_str(&content).unwrap();
+		let prdoc = crate::model::PrDoc::try_from(&value).unwrap();
+		let wrapper = DocFileWrapper::new(
+			"pr_1225.prdoc".into(),
+			crate::doc_filename::DocFileName::new(1225, None),
+			Some((value, prdoc)),
+		);
+
+		let selector = Selector::parse(".doc_filename.number").unwrap();
+		assert_eq!(vec![json!(1225)], selector.select_all(&[wrapper]).unwrap());
+	}
+}
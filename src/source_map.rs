@@ -0,0 +1,238 @@
+//! Map the nodes of a YAML document to their location in the source.
+//!
+//! Nodes are identified by their path, using the same notation as the JSON Schema validator: keys
+//! and indexes joined with `/`, the root being the empty string. For instance the description of
+//! the first `doc` entry is `/doc/0/description`.
+
+use saphyr_parser::{Event, Parser, Span};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A position in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct Position {
+	/// Line, starting at 1
+	pub line: usize,
+
+	/// Column in characters, starting at 1
+	pub column: usize,
+
+	/// Offset in bytes from the beginning of the source
+	pub offset: usize,
+}
+
+/// A range in the source. The end is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct Range {
+	/// Start of the range
+	pub start: Position,
+
+	/// End of the range
+	pub end: Position,
+}
+
+/// Location of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Node {
+	/// Location of the key, if the node is the value of a mapping
+	pub key: Option<Range>,
+
+	/// Location of the value
+	pub value: Range,
+}
+
+/// Locations of all the nodes of a YAML document
+#[derive(Debug, Default)]
+pub struct SourceMap {
+	nodes: BTreeMap<String, Node>,
+}
+
+impl SourceMap {
+	/// Parse a YAML source. Only the first document of the stream is mapped.
+	/// Returns `None` if the source is not valid YAML.
+	pub fn parse(source: &str) -> Option<Self> {
+		let events = Parser::new_from_str(source).collect::<Result<Vec<_>, _>>().ok()?;
+		// The parser counts characters, offsets need to be converted to bytes
+		let offsets = (!source.is_ascii())
+			.then(|| source.char_indices().map(|(i, _)| i).chain([source.len()]).collect());
+		let mut builder = Builder { events, offsets, pos: 0, nodes: BTreeMap::new() };
+
+		while builder.pos < builder.events.len() {
+			let is_doc_start = matches!(builder.events[builder.pos].0, Event::DocumentStart(_));
+			builder.pos += 1;
+			if is_doc_start {
+				if !matches!(builder.peek(), Some(Event::DocumentEnd) | None) {
+					builder.node(Some(String::new()), None);
+				}
+				break;
+			}
+		}
+
+		Some(Self { nodes: builder.nodes })
+	}
+
+	/// Return the node located at `path`, if it exists in the document
+	pub fn get(&self, path: &str) -> Option<&Node> {
+		self.nodes.get(path)
+	}
+
+	/// Return the node located at `path` or, if it does not exist, its closest existing ancestor.
+	/// The path of the node that was found is returned alongside.
+	pub fn closest<'a>(&self, mut path: &'a str) -> Option<(&'a str, &Node)> {
+		loop {
+			if let Some(node) = self.nodes.get(path) {
+				return Some((path, node));
+			}
+			path = &path[..path.rfind('/')?];
+		}
+	}
+
+	/// Iterate over all the nodes, ordered by path
+	pub fn iter(&self) -> impl Iterator<Item = (&String, &Node)> {
+		self.nodes.iter()
+	}
+}
+
+struct Builder<'input> {
+	events: Vec<(Event<'input>, Span)>,
+	/// Byte offset of each character, only required for non ASCII sources
+	offsets: Option<Vec<usize>>,
+	pos: usize,
+	nodes: BTreeMap<String, Node>,
+}
+
+impl<'input> Builder<'input> {
+	fn peek(&self) -> Option<&Event<'input>> {
+		self.events.get(self.pos).map(|(event, _)| event)
+	}
+
+	fn range(&self, span: Span) -> Range {
+		let position = |m: saphyr_parser::Marker| Position {
+			line: m.line(),
+			column: m.col() + 1,
+			offset: match &self.offsets {
+				Some(offsets) => offsets.get(m.index()).copied().unwrap_or(m.index()),
+				None => m.index(),
+			},
+		};
+		Range { start: position(span.start), end: position(span.end) }
+	}
+
+	/// Consume the end event of a collection and return the end of the collection
+	fn close(&mut self, fallback: Position) -> Position {
+		let (_, span) = self.events[self.pos];
+		self.pos += 1;
+		// Flow collections end with a visible `]` or `}` while block collections have an empty
+		// span, potentially located after trailing comments.
+		if span.is_empty() {
+			fallback
+		} else {
+			self.range(span).end
+		}
+	}
+
+	/// Consume a node and its children. Nodes without path, such as complex keys, are consumed
+	/// without being recorded.
+	fn node(&mut self, path: Option<String>, key: Option<Range>) -> Range {
+		let Some((event, span)) = self.events.get(self.pos).cloned() else {
+			return self.range(Span::default());
+		};
+		self.pos += 1;
+		let mut range = self.range(span);
+		let child_path = |segment: &str| path.as_ref().map(|p| format!("{p}/{segment}"));
+
+		match event {
+			Event::SequenceStart(..) => {
+				let mut index = 0;
+				while !matches!(self.peek(), Some(Event::SequenceEnd) | None) {
+					let child = self.node(child_path(&index.to_string()), None);
+					range.end = child.end;
+					index += 1;
+				}
+				range.end = self.close(range.end);
+			},
+			Event::MappingStart(..) => {
+				while !matches!(self.peek(), Some(Event::MappingEnd) | None) {
+					let name = match self.peek() {
+						Some(Event::Scalar(name, ..)) => Some(name.to_string()),
+						_ => None,
+					};
+					let key_range = self.node(None, None);
+					let child = self.node(name.and_then(|n| child_path(&n)), Some(key_range));
+					range.end = child.end;
+				}
+				range.end = self.close(range.end);
+			},
+			_ => {},
+		}
+
+		if let Some(path) = path {
+			self.nodes.insert(path, Node { key, value: range });
+		}
+		range
+	}
+}
+
+#[cfg(test)]
+mod test_source_map {
+	use super::*;
+
+	const SOURCE: &str = r#"# Comment
+title: Foobar
+
+doc:
+  - audience: Node Dev
+    description: &desc |
+      some text
+  - audience: Validator
+    description: *desc
+
+crates: []
+"#;
+
+	#[test]
+	fn test_scalars() {
+		let map = SourceMap::parse(SOURCE).unwrap();
+
+		let title = map.get("/title").unwrap();
+		assert_eq!((2, 8), (title.value.start.line, title.value.start.column));
+		assert_eq!((2, 1), (title.key.unwrap().start.line, title.key.unwrap().start.column));
+
+		let audience = map.get("/doc/1/audience").unwrap();
+		assert_eq!((8, 15), (audience.value.start.line, audience.value.start.column));
+		assert_eq!("Validator", &SOURCE[audience.value.start.offset..audience.value.end.offset]);
+	}
+
+	#[test]
+	fn test_collections() {
+		let map = SourceMap::parse(SOURCE).unwrap();
+
+		let crates = map.get("/crates").unwrap();
+		assert_eq!("[]", &SOURCE[crates.value.start.offset..crates.value.end.offset]);
+
+		let alias = map.get("/doc/1/description").unwrap();
+		assert_eq!("*desc", &SOURCE[alias.value.start.offset..alias.value.end.offset]);
+	}
+
+	#[test]
+	fn test_non_ascii() {
+		let source = "title: Fööbar 😀\ncrates: []\n";
+		let map = SourceMap::parse(source).unwrap();
+
+		let title = map.get("/title").unwrap();
+		assert_eq!("Fööbar 😀", &source[title.value.start.offset..title.value.end.offset]);
+		let crates = map.get("/crates").unwrap();
+		assert_eq!("[]", &source[crates.value.start.offset..crates.value.end.offset]);
+		assert_eq!((2, 9), (crates.value.start.line, crates.value.start.column));
+	}
+
+	#[test]
+	fn test_closest() {
+		let map = SourceMap::parse(SOURCE).unwrap();
+
+		let (path, node) = map.closest("/doc/0/notes").unwrap();
+		assert_eq!("/doc/0", path);
+		assert_eq!(5, node.value.start.line);
+		assert_eq!("", map.closest("/migrations").unwrap().0);
+	}
+}
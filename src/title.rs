@@ -13,6 +13,17 @@ impl Title {
 	pub fn as_os_string(&self) -> OsString {
 		OsString::from(self.0.replace(' ', "_"))
 	}
+
+	/// Return the slug of the title as used in canonical filenames: words made of alphanumeric
+	/// characters and inner dashes, separated by single underscores.
+	pub fn slug(&self) -> String {
+		self.0
+			.split(|c: char| !(c.is_alphanumeric() || c == '-'))
+			.map(|word| word.trim_matches('-'))
+			.filter(|word| !word.is_empty())
+			.collect::<Vec<_>>()
+			.join("_")
+	}
 }
 
 impl Display for Title {
@@ -54,6 +65,13 @@ mod test_super {
 		assert_eq!(OsString::from("foo_bar_😀"), Title::from("foo bar 😀").as_os_string());
 	}
 
+	#[test]
+	fn test_slug() {
+		assert_eq!("junk", Title::from(" - junk").slug());
+		assert_eq!("some_test_minimal", Title::from("_some_test_minimal").slug());
+		assert_eq!("host-fn_v2", Title::from("host-fn (v2)").slug());
+	}
+
 	#[test]
 	fn test_from_os_string() {
 		assert_eq!(
@@ -1,27 +1,115 @@
 //! Set of utils
-use crate::{common::PRNumber, config::PRDocConfig, error};
+use crate::{
+	common::PRNumber,
+	config::PRDocConfig,
+	error::{self, PRdocLibError},
+};
 use std::{
-	env,
-	fs::read_dir,
-	io::{self, ErrorKind},
-	path::PathBuf,
+	collections::BTreeMap,
+	convert::Infallible,
+	env, fs,
+	io::ErrorKind,
+	num::NonZeroUsize,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		mpsc,
+	},
+	thread,
 };
 
 /// Type alias for the result of parsing a file
 pub type ParseResult = (String, bool, Option<PRNumber>);
 
+/// Resolve a `file` given relatively to one of the PRDoc `dirs`. The first directory containing
+/// the file is used, the first directory otherwise.
+pub fn resolve_file(file: PathBuf, dirs: &[PathBuf]) -> PathBuf {
+	if file.is_absolute() {
+		return file;
+	}
+
+	let mut candidates = dirs.iter().map(|dir| dir.join(&file));
+	let first = candidates.clone().next();
+	candidates.find(|candidate| candidate.exists()).or(first).unwrap_or(file)
+}
+
+/// Return the default number of jobs, ie the number of CPUs
+pub fn default_jobs() -> usize {
+	thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1)
+}
+
+/// Apply `f` to all the `items` using up to `jobs` threads. The results are returned in the order
+/// of the `items`, whatever the number of jobs.
+pub fn par_map<T: Sync, R: Send>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+	let mut results = Vec::with_capacity(items.len());
+	let _ = par_for_each(items, jobs, f, |result| {
+		results.push(result);
+		Ok::<_, Infallible>(())
+	});
+	results
+}
+
+/// Apply `f` to all the `items` using up to `jobs` threads and pass the results to `on_result` in
+/// the order of the `items`, each one as soon as it and the ones before it are ready. Stops at the
+/// first error returned by `on_result`.
+pub fn par_for_each<T: Sync, R: Send, E>(
+	items: &[T],
+	jobs: usize,
+	f: impl Fn(&T) -> R + Sync,
+	mut on_result: impl FnMut(R) -> Result<(), E>,
+) -> Result<(), E> {
+	let jobs = jobs.min(items.len());
+	if jobs <= 1 {
+		return items.iter().try_for_each(|item| on_result(f(item)));
+	}
+
+	// Each worker picks the next item until none are left
+	let next = AtomicUsize::new(0);
+	thread::scope(|s| {
+		let (sender, receiver) = mpsc::channel();
+		for _ in 0..jobs {
+			let (sender, next, f) = (sender.clone(), &next, &f);
+			s.spawn(move || loop {
+				let index = next.fetch_add(1, Ordering::Relaxed);
+				let Some(item) = items.get(index) else { break };
+				// The receiver is dropped when `on_result` fails, there is no need to go on
+				if sender.send((index, f(item))).is_err() {
+					break;
+				}
+			});
+		}
+		drop(sender);
+
+		// The results coming early wait for the ones before them
+		let mut ready = BTreeMap::new();
+		let mut expected = 0;
+		for (index, result) in receiver {
+			ready.insert(index, result);
+			while let Some(result) = ready.remove(&expected) {
+				on_result(result)?;
+				expected += 1;
+			}
+		}
+		Ok(())
+	})
+}
+
 /// Get the project root (relative to closest Cargo.lock file)
-pub fn get_project_root() -> io::Result<PathBuf> {
+pub fn get_project_root() -> error::Result<PathBuf> {
 	let path = env::current_dir()?;
-	let path_ancestors = path.as_path().ancestors();
 
-	for p in path_ancestors {
-		let has_cargo = read_dir(p)?.any(|p| p.unwrap().file_name() == "Cargo.lock");
-		if has_cargo {
-			return Ok(PathBuf::from(p));
-		}
-	}
-	Err(io::Error::new(ErrorKind::NotFound, "Ran out of places to find Cargo.toml"))
+	path.ancestors()
+		.find(|p| p.join("Cargo.lock").is_file())
+		.map(PathBuf::from)
+		.ok_or(PRdocLibError::ProjectRootNotFound(path.clone()))
+}
+
+/// List the entries of a PRDoc folder, reporting a missing folder as such
+pub(crate) fn read_dir<P: AsRef<Path>>(dir: P) -> error::Result<fs::ReadDir> {
+	fs::read_dir(&dir).map_err(|e| match e.kind() {
+		ErrorKind::NotFound => PRdocLibError::MissingDirectory(dir.as_ref().to_path_buf()),
+		_ => PRdocLibError::IO(e),
+	})
 }
 
 pub(crate) fn get_numbers_from_file(file: &PathBuf) -> error::Result<Vec<ParseResult>> {
@@ -50,6 +138,72 @@ pub fn get_template_path(config: &PRDocConfig) -> PathBuf {
 	config.template.clone()
 }
 
+/// A temporary folder for the tests. Each call gets its own folder so that tests running
+/// concurrently, in the same or in different processes, do not interfere. The folder is removed
+/// when dropped.
+#[cfg(test)]
+pub(crate) struct TestDir(PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+	/// Create an empty folder whose name starts with `prdoc-{name}`
+	pub(crate) fn new(name: &str) -> Self {
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+		let unique = COUNTER.fetch_add(1, Ordering::Relaxed);
+		let dir = env::temp_dir().join(format!("prdoc-{name}-{}-{unique}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).expect("The temporary folder can be created");
+		Self(dir)
+	}
+
+	/// Write `content` to the file at `path`, relative to the folder, creating its parents
+	pub(crate) fn write(&self, path: &str, content: &str) -> PathBuf {
+		let path = self.0.join(path);
+		fs::create_dir_all(path.parent().expect("The file is in the folder"))
+			.expect("The parent folders can be created");
+		fs::write(&path, content).expect("The file can be written");
+		path
+	}
+
+	/// Run git in the folder and panic if it fails
+	pub(crate) fn git(&self, args: &[&str]) {
+		let output = std::process::Command::new("git")
+			.args(["-c", "user.name=prdoc", "-c", "user.email=prdoc@example.com", "-C"])
+			.arg(&self.0)
+			.args(args)
+			.output()
+			.expect("git can run");
+		assert!(
+			output.status.success(),
+			"git {args:?}: {}",
+			String::from_utf8_lossy(&output.stderr)
+		);
+	}
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TestDir {
+	type Target = PathBuf;
+
+	fn deref(&self) -> &PathBuf {
+		&self.0
+	}
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TestDir {
+	fn as_ref(&self) -> &Path {
+		&self.0
+	}
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.0);
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -60,4 +214,45 @@ mod tests {
 		let toml_path = project_root.to_str().unwrap().to_owned() + "/Cargo.toml";
 		assert!(!toml_path.is_empty());
 	}
+
+	#[test]
+	fn test_par_map() {
+		let items: Vec<usize> = (0..100).collect();
+		let expected: Vec<usize> = items.iter().map(|i| i * 2).collect();
+		for jobs in [0, 1, 3, 8, 200] {
+			assert_eq!(expected, par_map(&items, jobs, |i| i * 2));
+		}
+		assert!(par_map(&Vec::<usize>::new(), 4, |i| *i).is_empty());
+	}
+
+	#[test]
+	fn test_par_for_each() {
+		let items: Vec<usize> = (0..100).collect();
+		for jobs in [1, 3, 8] {
+			let mut seen = vec![];
+			let result = par_for_each(
+				&items,
+				jobs,
+				|i| i * 2,
+				|i| {
+					if i == 100 {
+						return Err(i);
+					}
+					seen.push(i);
+					Ok(())
+				},
+			);
+			assert_eq!(Err(100), result);
+			assert_eq!((0..50).map(|i| i * 2).collect::<Vec<_>>(), seen);
+		}
+	}
+
+	#[test]
+	fn test_read_dir() {
+		assert!(read_dir(Path::new("tests/data/all")).is_ok());
+
+		let error = read_dir(Path::new("tests/data/missing")).unwrap_err();
+		assert!(matches!(error, PRdocLibError::MissingDirectory(_)));
+		assert_eq!(exitcode::NOINPUT, error.exit_code());
+	}
 }
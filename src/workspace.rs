@@ -0,0 +1,197 @@
+//! Offline view of the crates of a Cargo workspace.
+//!
+//! The manifests are parsed directly, starting from the `Cargo.toml` found at the root of the
+//! project. Neither `cargo` nor the network are required.
+
+use crate::error::{PRdocLibError, Result};
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+};
+use toml::Value;
+
+/// A crate of the workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceCrate {
+	/// Path of the manifest of the crate
+	pub manifest: PathBuf,
+
+	/// Whether the crate can be published, `false` when the manifest has `publish = false`
+	pub publish: bool,
+}
+
+/// The crates of a Cargo workspace, by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workspace {
+	crates: BTreeMap<String, WorkspaceCrate>,
+}
+
+impl Workspace {
+	/// Load the workspace whose root manifest is located in `root`. A manifest without
+	/// `[workspace]` section is handled as a workspace made of a single crate.
+	pub fn load(root: &Path) -> Result<Self> {
+		let manifest = root.join("Cargo.toml");
+		let root_manifest = read_manifest(&manifest)?;
+		let workspace = root_manifest.get("workspace");
+		let inherited_publish = workspace
+			.and_then(|w| w.get("package"))
+			.and_then(|p| p.get("publish"))
+			.map(is_published)
+			.unwrap_or(true);
+
+		let mut manifests = vec![];
+		if root_manifest.get("package").is_some() {
+			manifests.push(manifest);
+		}
+
+		if let Some(workspace) = workspace {
+			let patterns = |name: &str| -> Vec<String> {
+				workspace
+					.get(name)
+					.and_then(|m| m.as_array())
+					.map(|a| a.iter().filter_map(|m| m.as_str()).map(String::from).collect())
+					.unwrap_or_default()
+			};
+			let excluded: Vec<PathBuf> = patterns("exclude").iter().map(|e| root.join(e)).collect();
+
+			for pattern in patterns("members") {
+				let pattern = root.join(&pattern);
+				let paths = glob::glob(&pattern.to_string_lossy()).map_err(|e| {
+					PRdocLibError::InvalidPattern(pattern.display().to_string(), e.to_string())
+				})?;
+
+				for dir in paths.filter_map(|p| p.ok()) {
+					let manifest = dir.join("Cargo.toml");
+					if manifest.is_file() && !excluded.iter().any(|e| dir.starts_with(e)) {
+						manifests.push(manifest);
+					}
+				}
+			}
+		}
+
+		let mut crates = BTreeMap::new();
+		for manifest in manifests {
+			let content = read_manifest(&manifest)?;
+			let Some(package) = content.get("package") else { continue };
+			let Some(name) = package.get("name").and_then(|n| n.as_str()) else { continue };
+
+			let publish = match package.get("publish") {
+				Some(Value::Table(t))
+					if t.get("workspace").and_then(|w| w.as_bool()) == Some(true) =>
+					inherited_publish,
+				Some(publish) => is_published(publish),
+				None => true,
+			};
+			crates.insert(name.to_string(), WorkspaceCrate { manifest, publish });
+		}
+
+		log::debug!("Found {} crates in the workspace", crates.len());
+		Ok(Self { crates })
+	}
+
+	/// Return the crate named `name`, if it is part of the workspace
+	pub fn get(&self, name: &str) -> Option<&WorkspaceCrate> {
+		self.crates.get(name)
+	}
+
+	/// Iterate over the crates of the workspace, ordered by name
+	pub fn iter(&self) -> impl Iterator<Item = (&String, &WorkspaceCrate)> {
+		self.crates.iter()
+	}
+
+	/// Return up to 3 crates with a name similar to `name`, the closest first
+	pub fn suggest(&self, name: &str) -> Vec<&str> {
+		let max_distance = (name.chars().count() / 3).max(2);
+		let mut candidates: Vec<(usize, &str)> = self
+			.crates
+			.keys()
+			.map(|candidate| (levenshtein(name, candidate), candidate.as_str()))
+			.filter(|(distance, _)| *distance <= max_distance)
+			.collect();
+		candidates.sort();
+		candidates.into_iter().take(3).map(|(_, candidate)| candidate).collect()
+	}
+}
+
+fn read_manifest(manifest: &Path) -> Result<Value> {
+	let content = fs::read_to_string(manifest)?;
+	toml::from_str(&content).map_err(|_| PRdocLibError::InvalidManifest(manifest.to_path_buf()))
+}
+
+/// `publish` is either a boolean or the list of registries the crate can be published to
+fn is_published(publish: &Value) -> bool {
+	match publish {
+		Value::Boolean(publish) => *publish,
+		Value::Array(registries) => !registries.is_empty(),
+		_ => true,
+	}
+}
+
+/// Number of single character edits required to change `a` into `b`
+fn levenshtein(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+	for (i, ca) in a.chars().enumerate() {
+		let mut current = vec![i + 1];
+		for (j, cb) in b.iter().enumerate() {
+			let substitution = previous[j] + usize::from(ca != *cb);
+			current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+		}
+		previous = current;
+	}
+	previous[b.len()]
+}
+
+#[cfg(test)]
+mod test_workspace {
+	use super::*;
+	use crate::utils::TestDir;
+
+	/// Create a workspace in a temporary folder
+	fn workspace() -> TestDir {
+		let root = TestDir::new("workspace");
+		root.write(
+			"Cargo.toml",
+			"[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\nexclude = [\"crates/old\"]\n\n[workspace.package]\npublish = false\n",
+		);
+		root.write("crates/sp-foobar-a/Cargo.toml", "[package]\nname = \"sp-foobar-a\"\n");
+		root.write(
+			"crates/sp-foobar-b/Cargo.toml",
+			"[package]\nname = \"sp-foobar-b\"\npublish = []\n",
+		);
+		root.write("crates/old/Cargo.toml", "[package]\nname = \"old\"\n");
+		root.write("crates/README.md", "Not a crate");
+		root.write("tools/cli/Cargo.toml", "[package]\nname = \"cli\"\npublish.workspace = true\n");
+		root
+	}
+
+	#[test]
+	fn test_load() {
+		let ws = Workspace::load(&workspace()).unwrap();
+
+		assert_eq!(
+			vec!["cli", "sp-foobar-a", "sp-foobar-b"],
+			ws.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>()
+		);
+		assert!(ws.get("sp-foobar-a").unwrap().publish);
+		assert!(!ws.get("sp-foobar-b").unwrap().publish);
+		assert!(!ws.get("cli").unwrap().publish);
+	}
+
+	#[test]
+	fn test_single_crate() {
+		let ws = Workspace::load(Path::new(".")).unwrap();
+		assert!(ws.get("parity-prdoc").is_some());
+	}
+
+	#[test]
+	fn test_suggest() {
+		let ws = Workspace::load(&workspace()).unwrap();
+		assert_eq!(vec!["sp-foobar-a", "sp-foobar-b"], ws.suggest("sp-foobar-c"));
+		assert_eq!(vec!["cli"], ws.suggest("clo"));
+		assert!(ws.suggest("pallet-balances").is_empty());
+		assert_eq!(3, levenshtein("kitten", "sitting"));
+	}
+}
@@ -0,0 +1,266 @@
+//! Edit YAML documents while preserving their formatting.
+//!
+//! Serializing a document with `serde_yaml` loses the comments, the anchors and the layout chosen
+//! by the authors. The [YamlEditor] instead edits the source text directly, relying on the
+//! [SourceMap] to locate the nodes. Nodes are identified by their path, ie `/doc/0/audience`.
+
+use crate::{
+	error::{PRdocLibError, Result},
+	source_map::{Node, SourceMap},
+};
+
+/// Editor of a YAML document
+#[derive(Debug)]
+pub struct YamlEditor {
+	source: String,
+	map: SourceMap,
+}
+
+impl YamlEditor {
+	/// Create an editor for the YAML `source`
+	pub fn new(source: impl Into<String>) -> Result<Self> {
+		let source = source.into();
+		let map = Self::parse(&source)?;
+		Ok(Self { source, map })
+	}
+
+	fn parse(source: &str) -> Result<SourceMap> {
+		// serde_yaml provides a much better error than the source map
+		let _: serde_yaml::Value = serde_yaml::from_str(source)?;
+		SourceMap::parse(source).ok_or(PRdocLibError::Unknown)
+	}
+
+	/// Return the current source of the document
+	pub fn source(&self) -> &str {
+		&self.source
+	}
+
+	/// Consume the editor and return the source of the document
+	pub fn into_source(self) -> String {
+		self.source
+	}
+
+	/// Return the current content of the document
+	pub fn value(&self) -> Result<serde_yaml::Value> {
+		let mut value: serde_yaml::Value = serde_yaml::from_str(&self.source)?;
+		value.apply_merge()?;
+		Ok(value)
+	}
+
+	/// Return whether a node exists at `path`
+	pub fn contains(&self, path: &str) -> bool {
+		self.map.get(path).is_some()
+	}
+
+	fn node(&self, path: &str) -> Result<Node> {
+		self.map
+			.get(path)
+			.copied()
+			.ok_or_else(|| Self::error(path, "the node does not exist"))
+	}
+
+	fn error(path: &str, reason: &str) -> PRdocLibError {
+		PRdocLibError::EditFailed(path.to_string(), reason.to_string())
+	}
+
+	/// Replace the bytes between `start` and `end` and refresh the source map
+	fn splice(&mut self, start: usize, end: usize, text: &str, path: &str) -> Result<()> {
+		let mut source = self.source.clone();
+		source.replace_range(start..end, text);
+		self.map = Self::parse(&source)
+			.map_err(|e| Self::error(path, &format!("the result is not valid YAML: {e}")))?;
+		self.source = source;
+		Ok(())
+	}
+
+	/// Return the offset of the beginning of the line containing `offset`
+	fn line_start(&self, offset: usize) -> usize {
+		self.source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0)
+	}
+
+	/// Return the offset of the beginning of the line following `offset`
+	fn next_line(&self, offset: usize) -> usize {
+		self.source[offset..]
+			.find('\n')
+			.map(|i| offset + i + 1)
+			.unwrap_or(self.source.len())
+	}
+
+	/// Rename the key of the mapping entry at `path`
+	pub fn rename_key(&mut self, path: &str, name: &str) -> Result<()> {
+		let key = self.node(path)?.key.ok_or_else(|| Self::error(path, "the node has no key"))?;
+		self.splice(key.start.offset, key.end.offset, &scalar(name), path)
+	}
+
+	/// Replace the value of the scalar at `path`. The value is quoted when required.
+	pub fn set_scalar(&mut self, path: &str, value: &str) -> Result<()> {
+		let node = self.node(path)?;
+		if self.map.iter().any(|(p, _)| p.starts_with(&format!("{path}/"))) {
+			return Err(Self::error(path, "the node is not a scalar"));
+		}
+		self.splice(node.value.start.offset, node.value.end.offset, &scalar(value), path)
+	}
+
+	/// Replace the scalar at `path`, which may be empty, with the raw YAML `value`
+	pub fn set_raw(&mut self, path: &str, value: &str) -> Result<()> {
+		let node = self.node(path)?;
+		if self.map.iter().any(|(p, _)| p.starts_with(&format!("{path}/"))) {
+			return Err(Self::error(path, "the node is not a scalar"));
+		}
+
+		let (mut start, mut end) = (node.value.start.offset, node.value.end.offset);
+		// Empty values are located on their key, the value goes after the colon
+		if let Some(key) = node.key.filter(|_| start == end) {
+			let colon = self.source[key.end.offset..]
+				.find(':')
+				.ok_or_else(|| Self::error(path, "the key has no value"))?;
+			start = key.end.offset + colon + 1;
+			end = start;
+		}
+		let separator = if self.source[..start].ends_with(':') { " " } else { "" };
+		self.splice(start, end, &format!("{separator}{value}"), path)
+	}
+
+	/// Remove the entry at `path` from its block mapping or block sequence, including the lines
+	/// it spans.
+	pub fn remove(&mut self, path: &str) -> Result<()> {
+		let node = self.node(path)?;
+		let start = node.key.unwrap_or(node.value).start.offset;
+		let line_start = self.line_start(start);
+		let prefix = &self.source[line_start..start];
+		let is_item = path.rsplit('/').next().is_some_and(|s| s.parse::<usize>().is_ok());
+
+		let allowed = if is_item { prefix.trim() == "-" } else { prefix.trim().is_empty() };
+		if !allowed {
+			return Err(Self::error(path, "only entries starting their own line can be removed"));
+		}
+
+		let end = if node.value.end.column == 1 {
+			node.value.end.offset
+		} else {
+			self.next_line(node.value.end.offset)
+		};
+		self.splice(line_start, end, "", path)
+	}
+
+	/// Add the `key` with the raw YAML `value` at the end of the block mapping at `path`
+	pub fn insert(&mut self, path: &str, key: &str, value: &str) -> Result<()> {
+		let node = self.node(path)?;
+		let prefix = format!("{path}/");
+		let child = self
+			.map
+			.iter()
+			.filter(|(p, _)| p.strip_prefix(&prefix).is_some_and(|rest| !rest.contains('/')))
+			.find_map(|(_, n)| n.key)
+			.ok_or_else(|| Self::error(path, "the node is not a non empty mapping"))?;
+
+		if self.contains(&format!("{prefix}{key}")) {
+			return Err(Self::error(path, &format!("the key `{key}` already exists")));
+		}
+
+		let indent = " ".repeat(child.start.column - 1);
+		let at = if node.value.end.column == 1 {
+			node.value.end.offset
+		} else {
+			self.next_line(node.value.end.offset)
+		};
+		let newline =
+			if at == self.source.len() && !self.source.ends_with('\n') { "\n" } else { "" };
+		let text = format!("{newline}{indent}{}: {value}\n", scalar(key));
+		self.splice(at, at, &text, path)
+	}
+}
+
+/// Render a string as a YAML scalar, quoting it only when required
+fn scalar(value: &str) -> String {
+	let plain = !value.is_empty() &&
+		!value.contains('\n') &&
+		value.trim() == value &&
+		serde_yaml::from_str::<serde_yaml::Value>(value).ok() ==
+			Some(serde_yaml::Value::String(value.to_string()));
+
+	if plain {
+		value.to_string()
+	} else {
+		serde_json::Value::String(value.to_string()).to_string()
+	}
+}
+
+#[cfg(test)]
+mod test_yaml_edit {
+	use super::*;
+
+	const SOURCE: &str = r#"# Comment
+title: Foobar # inline
+
+doc:
+  - audience: Node Dev
+    description: &desc |
+      some text
+  - audience: Validator
+    description: *desc
+
+crates:
+  - name: foo
+    semver: patch
+"#;
+
+	#[test]
+	fn test_set_scalar() {
+		let mut editor = YamlEditor::new(SOURCE).unwrap();
+		editor.set_scalar("/doc/0/audience", "Node Operator").unwrap();
+		editor.set_scalar("/crates/0/semver", "no change").unwrap();
+		editor.set_scalar("/title", "true").unwrap();
+
+		let source = editor.source();
+		assert!(source.contains("  - audience: Node Operator\n"));
+		assert!(source.contains("semver: no change\n"));
+		assert!(source.contains("title: \"true\" # inline\n"));
+		assert!(source.contains("description: *desc\n"));
+		assert!(editor.set_scalar("/doc/0", "foo").is_err());
+	}
+
+	#[test]
+	fn test_rename_key() {
+		let mut editor = YamlEditor::new(SOURCE).unwrap();
+		editor.rename_key("/crates/0/semver", "bump").unwrap();
+		assert!(editor.source().contains("    bump: patch\n"));
+		assert_eq!(editor.value().unwrap()["crates"][0]["bump"], "patch");
+	}
+
+	#[test]
+	fn test_set_raw() {
+		let mut editor = YamlEditor::new("title: Foo\nhost_functions:\ncrates: ~\n").unwrap();
+		editor.set_raw("/host_functions", "[]").unwrap();
+		editor.set_raw("/crates", "[]").unwrap();
+
+		assert_eq!("title: Foo\nhost_functions: []\ncrates: []\n", editor.source());
+		assert!(editor.set_raw("/doc", "[]").is_err());
+	}
+
+	#[test]
+	fn test_remove() {
+		let mut editor = YamlEditor::new(SOURCE).unwrap();
+		editor.remove("/doc/1").unwrap();
+		editor.remove("/crates/0/semver").unwrap();
+		assert!(editor.remove("/crates/0/name").is_err());
+
+		let value = editor.value().unwrap();
+		assert_eq!(1, value["doc"].as_sequence().unwrap().len());
+		assert!(value["crates"][0].get("semver").is_none());
+		assert!(editor.source().starts_with("# Comment\n"));
+	}
+
+	#[test]
+	fn test_insert() {
+		let mut editor = YamlEditor::new(SOURCE).unwrap();
+		editor.insert("/crates/0", "note", "Some note").unwrap();
+		editor.insert("", "tags", "[]").unwrap();
+		assert!(editor.insert("/crates/0", "name", "bar").is_err());
+
+		let value = editor.value().unwrap();
+		assert_eq!(value["crates"][0]["note"], "Some note");
+		assert!(value["tags"].as_sequence().unwrap().is_empty());
+		assert!(editor.source().contains("    semver: patch\n    note: Some note\n"));
+	}
+}